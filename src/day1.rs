@@ -1,40 +1,115 @@
+use crate::runner::{parse_lines, Puzzle};
 use std::collections::HashSet;
 
-fn find_first_duplicate(xs: &[i32]) -> i32 {
-    let mut cur = 0;
+// The first frequency reached twice while applying `xs` over and over, or `None` if no frequency
+// ever repeats.
+fn find_first_duplicate(xs: &[i32]) -> Option<i64> {
+    // sums[i] is the frequency before applying xs[i] on the first pass. Pass k reaches
+    // sums[i] + k * total instead, at time k * n + i.
+    let n = xs.len();
+    let mut sums = Vec::with_capacity(n);
+    let mut cur: i64 = 0;
+    for &x in xs {
+        sums.push(cur);
+        cur += x as i64;
+    }
+    let total = cur;
+
+    // Any repeat within the first pass comes before every repeat in later passes.
     let mut seen = HashSet::new();
-    loop {
-        for x in xs {
-            if seen.contains(&cur) {
-                return cur;
-            }
-            seen.insert(cur);
-            cur += x;
+    for &s in &sums {
+        if !seen.insert(s) {
+            return Some(s);
+        }
+    }
+    if n == 0 {
+        return None;
+    } else if total == 0 {
+        return Some(0);
+    }
+
+    // Flip the sums around so the frequencies always drift upwards by `m` per pass. Then
+    // sums[a] repeats sums[b] after d passes iff they are congruent mod m and
+    // sums[b] = sums[a] + d * m.
+    let m = total.abs();
+    let drifted: Vec<i64> = sums.iter().map(|&s| s * total.signum()).collect();
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by_key(|&i| (((drifted[i] % m) + m) % m, drifted[i]));
+
+    // Only adjacent sums in the same residue class can be the first to collide.
+    let mut first: Option<(i64, usize)> = None;
+    for pair in order.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        let d = drifted[b] - drifted[a];
+        if d % m != 0 {
+            continue;
         }
+        let time = (d / m) * n as i64 + a as i64;
+        if first.is_none_or(|(t, _)| time < t) {
+            first = Some((time, b));
+        }
+    }
+    first.map(|(_, b)| sums[b])
+}
+
+pub(crate) struct Day1;
+
+impl Puzzle for Day1 {
+    type Input = Vec<i32>;
+
+    fn parse(s: &str) -> Result<Vec<i32>, String> {
+        parse_lines(s)
+    }
+
+    fn part1(changes: &Vec<i32>) -> Result<String, String> {
+        Ok(changes.iter().sum::<i32>().to_string())
+    }
+
+    fn part2(changes: &Vec<i32>) -> Result<String, String> {
+        find_first_duplicate(changes)
+            .map(|f| f.to_string())
+            .ok_or_else(|| String::from("no frequency is ever reached twice"))
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use lazy_static::lazy_static;
-    use std::fs;
+    use crate::runner;
 
-    lazy_static! {
-        static ref INPUTS: Vec<i32> = fs::read_to_string("data/day1/input")
-            .expect("read input file")
-            .lines()
-            .map(|s| s.parse().expect("parse line to i32"))
-            .collect();
+    #[test]
+    fn part1() {
+        runner::check_answer::<Day1>(1, 1);
     }
 
     #[test]
-    fn part1() {
-        assert_eq!(INPUTS.iter().cloned().sum::<i32>(), 402);
+    fn smoke() {
+        assert_eq!(find_first_duplicate(&[1, -2, 3, 1]), Some(2));
+        assert_eq!(find_first_duplicate(&[1, -1]), Some(0));
+        assert_eq!(find_first_duplicate(&[3, 3, 4, -2, -4]), Some(10));
+        assert_eq!(find_first_duplicate(&[-6, 3, 8, 5, -6]), Some(5));
+        assert_eq!(find_first_duplicate(&[7, 7, -2, -7, -4]), Some(14));
+        assert_eq!(find_first_duplicate(&[1, -2]), Some(0));
+    }
+
+    #[test]
+    fn no_duplicate() {
+        assert_eq!(find_first_duplicate(&[]), None);
+        assert_eq!(find_first_duplicate(&[1, 1]), None);
+        assert_eq!(find_first_duplicate(&[-5, 2]), None);
+    }
+
+    #[test]
+    fn beyond_i32() {
+        let max = i32::MAX;
+        assert_eq!(
+            find_first_duplicate(&[max, max, 1, -1]),
+            Some(2 * i64::from(max))
+        );
     }
 
     #[test]
     fn part2() {
-        assert_eq!(find_first_duplicate(&INPUTS), 481);
+        runner::check_answer::<Day1>(1, 2);
     }
 }
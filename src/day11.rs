@@ -1,3 +1,6 @@
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::runner::Puzzle;
 use std::ops::Range;
 
 fn power_level(x: i32, y: i32, serial_number: i32) -> i32 {
@@ -10,58 +13,54 @@ fn power_level(x: i32, y: i32, serial_number: i32) -> i32 {
 struct Square(usize, usize, usize); // x, y, size
 
 struct SummedAreaTable {
-    width: usize,
-    height: usize,
-    table: Vec<i32>,
+    // table(x, y) = Sum { grid(x', y') | x' <- 0..x, y' <- 0..y }
+    table: Grid<i32>,
 }
 
 impl SummedAreaTable {
-    fn new(width: usize, height: usize, values: Vec<i32>) -> SummedAreaTable {
-        assert_eq!(values.len(), width * height);
-        // table(i, j) = Sum { values(i', j') | i' <- 1..i, j' <- 1..j }
-        let mut table = vec![0; (width + 1) * (height + 1)];
-        for i in 1..=height {
-            for j in 1..=width {
-                table[(width + 1) * i + j] = table[(width + 1) * (i - 1) + j]
-                    + table[(width + 1) * i + (j - 1)]
-                    - table[(width + 1) * (i - 1) + (j - 1)]
-                    + values[width * (i - 1) + (j - 1)];
-            }
-        }
-        SummedAreaTable {
-            width,
-            height,
-            table,
+    // The table is indexed from (0, 0), regardless of where `grid` starts.
+    fn new(grid: &Grid<i32>) -> SummedAreaTable {
+        let mut table = Grid::new(grid.width() + 1, grid.height() + 1, 0);
+        for (p, &value) in grid.iter() {
+            let Point(x, y) = p - grid.origin() + Point(1, 1);
+            table[Point(x, y)] = table[Point(x, y - 1)] + table[Point(x - 1, y)]
+                - table[Point(x - 1, y - 1)]
+                + value;
         }
+        SummedAreaTable { table }
     }
 
+    // The sum over the half-open rectangle `xs` x `ys` of the original grid.
     fn get(&self, xs: Range<usize>, ys: Range<usize>) -> i32 {
-        self.table[(self.width + 1) * (xs.end - 1) + (ys.end - 1)]
-            - self.table[(self.width + 1) * (xs.end - 1) + (ys.start - 1)]
-            - self.table[(self.width + 1) * (xs.start - 1) + (ys.end - 1)]
-            + self.table[(self.width + 1) * (xs.start - 1) + (ys.start - 1)]
+        let at = |x: usize, y: usize| self.table[Point(x as i32, y as i32)];
+        at(xs.end, ys.end) - at(xs.start, ys.end) - at(xs.end, ys.start) + at(xs.start, ys.start)
     }
 }
 
-fn solve(serial_number: i32, grid_size: usize, sizes: Range<usize>) -> (Square, i32) {
-    let mut grid = vec![0; grid_size * grid_size];
-    for y in 1..=grid_size {
-        for x in 1..=grid_size {
-            grid[grid_size * (y - 1) + (x - 1)] = power_level(y as i32, x as i32, serial_number);
-        }
-    }
+// Fuel cells are 1-indexed, so the top-left cell of the grid is (1, 1).
+fn fuel_cells(serial_number: i32, width: usize, height: usize) -> Grid<i32> {
+    Grid::from_fn(width, height, |p| {
+        power_level(p.0 + 1, p.1 + 1, serial_number)
+    })
+    .with_origin(Point(1, 1))
+}
 
-    let table = SummedAreaTable::new(grid_size, grid_size, grid);
+fn solve(serial_number: i32, width: usize, height: usize, sizes: Range<usize>) -> (Square, i32) {
+    let grid = fuel_cells(serial_number, width, height);
+    let table = SummedAreaTable::new(&grid);
 
     let mut best_score = 0;
     let mut best_square = Square(0, 0, 0);
     for size in sizes {
-        for y in 1..=grid_size + 1 - size {
-            for x in 1..=grid_size + 1 - size {
+        if size > width || size > height {
+            break;
+        }
+        for y in 0..=height - size {
+            for x in 0..=width - size {
                 let score = table.get(x..x + size, y..y + size);
                 if score > best_score {
                     best_score = score;
-                    best_square = Square(x, y, size);
+                    best_square = Square(x + 1, y + 1, size);
                 }
             }
         }
@@ -70,26 +69,77 @@ fn solve(serial_number: i32, grid_size: usize, sizes: Range<usize>) -> (Square,
     (best_square, best_score)
 }
 
+pub(crate) struct Day11;
+
+impl Puzzle for Day11 {
+    type Input = i32;
+
+    fn parse(s: &str) -> Result<i32, String> {
+        s.trim()
+            .parse()
+            .map_err(|_| format!("could not parse serial number {}", s.trim()))
+    }
+
+    fn part1(&serial_number: &i32) -> Result<String, String> {
+        let (Square(x, y, _), _) = solve(serial_number, 300, 300, 3..4);
+        Ok(format!("{},{}", x, y))
+    }
+
+    fn part2(&serial_number: &i32) -> Result<String, String> {
+        let (Square(x, y, size), _) = solve(serial_number, 300, 300, 1..301);
+        Ok(format!("{},{},{}", x, y, size))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::runner;
     #[test]
     fn smoke() {
         assert_eq!(power_level(3, 5, 8), 4);
         assert_eq!(power_level(122, 79, 57), -5);
         assert_eq!(power_level(217, 196, 39), 0);
         assert_eq!(power_level(101, 153, 71), 4);
-        assert_eq!(solve(18, 300, 3..4), (Square(33, 45, 3), 29));
-        assert_eq!(solve(42, 300, 3..4), (Square(21, 61, 3), 30));
+        assert_eq!(solve(18, 300, 300, 3..4), (Square(33, 45, 3), 29));
+        assert_eq!(solve(42, 300, 300, 3..4), (Square(21, 61, 3), 30));
+    }
+
+    #[test]
+    fn non_square() {
+        let (width, height) = (7, 4);
+        let grid = fuel_cells(18, width, height);
+        assert_eq!(grid[Point(3, 1)], power_level(3, 1, 18));
+        assert_eq!(grid[Point(1, 4)], power_level(1, 4, 18));
+
+        let table = SummedAreaTable::new(&grid);
+        for y0 in 0..height {
+            for y1 in y0..=height {
+                for x0 in 0..width {
+                    for x1 in x0..=width {
+                        let expected: i32 = (y0..y1)
+                            .flat_map(|y| (x0..x1).map(move |x| (x, y)))
+                            .map(|(x, y)| grid[Point(x as i32 + 1, y as i32 + 1)])
+                            .sum();
+                        assert_eq!(table.get(x0..x1, y0..y1), expected);
+                    }
+                }
+            }
+        }
+
+        // A square can never be larger than the shorter side.
+        let (Square(x, y, size), _) = solve(18, width, height, 1..10);
+        assert!(size <= height);
+        assert!(x + size - 1 <= width && y + size - 1 <= height);
     }
 
     #[test]
     fn part1() {
-        assert_eq!(solve(7400, 300, 3..4), (Square(34, 72, 3), 29));
+        runner::check_answer::<Day11>(11, 1);
     }
 
     #[test]
     fn part2() {
-        assert_eq!(solve(7400, 300, 1..301), (Square(233, 187, 13), 91));
+        runner::check_answer::<Day11>(11, 2);
     }
 }
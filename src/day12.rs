@@ -1,82 +1,755 @@
-use std::collections::HashSet;
+use crate::runner::Puzzle;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashMap;
+use std::iter;
+use std::str::FromStr;
+
+// How many generations `State::sum_after` will simulate while looking for a cycle.
+const CYCLE_BUDGET: u64 = 1_000;
+
+// After `start` generations, the live pattern repeats every `period` generations, shifted
+// `offset` cells to the right each time.
+#[derive(Debug, Eq, PartialEq)]
+struct Cycle {
+    start: u64,
+    period: u64,
+    offset: i64,
+}
+
+// A one-dimensional cellular automaton rule. A cell's next state is looked up in `table` by its
+// neighborhood: the states of the `radius` cells on either side of it and itself, read as a
+// base-`states` number with the leftmost cell most significant.
+#[derive(Clone, Debug)]
+struct Rule {
+    radius: usize,
+    states: u8,
+    table: Vec<u8>,
+    // `table` extended to whole runs of `chunk` cells at a time, if the rule allows it (see
+    // `Chunks::new`).
+    chunks: Option<Chunks>,
+}
+
+// The bits each cell is packed into: the fewest that hold every state, rounded up to a power of
+// two so that cells never straddle two words.
+fn cell_bits(states: u8) -> usize {
+    (8 - (states - 1).leading_zeros()).next_power_of_two() as usize
+}
+
+// A lookup table that steps `chunk` cells at once. It's indexed by the packed bits of the
+// `chunk + 2 * radius` cells they depend on, and gives the packed bits of the `chunk` new cells.
+#[derive(Clone, Debug)]
+struct Chunks {
+    chunk: usize,
+    table: Vec<u16>,
+}
+
+impl Chunks {
+    // Only rules where every bit pattern is a state can be packed like this, and only when the
+    // window is small enough for the table (at most 2^16 entries) to cover at least 2 cells.
+    fn new(radius: usize, states: u8, table: &[u8]) -> Option<Chunks> {
+        let bits = cell_bits(states);
+        if states as usize != 1 << bits {
+            return None;
+        }
+        let most = (16 / bits).checked_sub(2 * radius)?;
+        if most < 2 {
+            return None;
+        }
+        // A power of two, so that a whole number of chunks fits in each word.
+        let chunk = 1 << (63 - (most as u64).leading_zeros());
+        let (window, mask) = (chunk + 2 * radius, (1 << bits) - 1);
+        let size = table.len();
+        let table = (0..1usize << (window * bits))
+            .map(|cells| {
+                let cell = |k: usize| (cells >> (bits * k)) & mask;
+                (0..chunk).fold(0, |out, k| {
+                    let idx = (k..k + 2 * radius + 1).fold(0, |idx, j| (idx << bits) | cell(j));
+                    out | u16::from(table[idx & (size - 1)]) << (bits * k)
+                })
+            })
+            .collect();
+        Some(Chunks { chunk, table })
+    }
+}
+
+impl Rule {
+    fn new(radius: usize, states: u8, table: Vec<u8>) -> Result<Rule, String> {
+        if states < 2 {
+            return Err(format!("need at least 2 states, got {}", states));
+        }
+        let size = (states as usize).pow(2 * radius as u32 + 1);
+        if table.len() != size {
+            return Err(format!(
+                "expected {} table entries for radius {} and {} states, got {}",
+                size,
+                radius,
+                states,
+                table.len()
+            ));
+        }
+        if let Some(&bad) = table.iter().find(|&&next| next >= states) {
+            return Err(format!("state {} is out of range", bad));
+        }
+        // Otherwise there would be infinitely many live cells after a single step.
+        if table[0] != 0 {
+            return Err(String::from("a dead neighborhood must stay dead"));
+        }
+        Ok(Rule {
+            radius,
+            states,
+            chunks: Chunks::new(radius, states, &table),
+            table,
+        })
+    }
+
+    // A two-state rule where exactly the `live` neighborhoods produce a live cell.
+    fn from_neighborhoods<I>(radius: usize, live: I) -> Result<Rule, String>
+    where
+        I: IntoIterator<Item = Vec<bool>>,
+    {
+        let mut table = vec![0; 1 << (2 * radius + 1)];
+        for neighborhood in live {
+            if neighborhood.len() != 2 * radius + 1 {
+                return Err(format!(
+                    "expected a neighborhood of {} cells, got {}",
+                    2 * radius + 1,
+                    neighborhood.len()
+                ));
+            }
+            let idx = neighborhood
+                .iter()
+                .fold(0, |acc, &alive| (acc << 1) | alive as usize);
+            table[idx] = 1;
+        }
+        Rule::new(radius, 2, table)
+    }
+
+    // One of Wolfram's elementary rules, e.g. rule 30 or rule 110.
+    fn elementary(number: u8) -> Result<Rule, String> {
+        Rule::new(1, 2, (0..8).map(|idx| (number >> idx) & 1).collect())
+    }
+}
 
 #[derive(Clone)]
-struct State {
-    alive: HashSet<i32>,
-    rules: HashSet<Vec<bool>>,
+pub(crate) struct State {
+    // Cell `i` is the one at `origin + i`, and is packed into `cells` using `bits` bits per
+    // cell. The cells are kept trimmed, so cell 0 is alive and the last word is nonzero (or
+    // there are no words at all).
+    origin: i64,
+    cells: Vec<u64>,
+    bits: usize,
+    rule: Rule,
 }
 
 impl State {
+    fn new<A>(rule: Rule, alive: A) -> State
+    where
+        A: IntoIterator<Item = i64>,
+    {
+        State::with_cells(rule, alive.into_iter().map(|i| (i, 1)))
+    }
+
+    fn with_cells<C>(rule: Rule, cells: C) -> State
+    where
+        C: IntoIterator<Item = (i64, u8)>,
+    {
+        let cells: Vec<(i64, u8)> = cells.into_iter().filter(|&(_, c)| c != 0).collect();
+        let mut state = State {
+            origin: cells.iter().map(|&(i, _)| i).min().unwrap_or(0),
+            cells: Vec::new(),
+            bits: cell_bits(rule.states),
+            rule,
+        };
+        for (i, c) in cells {
+            assert!(c < state.rule.states);
+            let idx = (i - state.origin) as usize;
+            state.set(idx, c);
+        }
+        state.trim();
+        state
+    }
+
+    fn per_word(&self) -> usize {
+        64 / self.bits
+    }
+
+    fn get(&self, idx: usize) -> u8 {
+        let mask = (1 << self.bits) - 1;
+        self.cells.get(idx / self.per_word()).map_or(0, |word| {
+            ((word >> (self.bits * (idx % self.per_word()))) & mask) as u8
+        })
+    }
+
+    fn set(&mut self, idx: usize, c: u8) {
+        let (w, shift) = (idx / self.per_word(), self.bits * (idx % self.per_word()));
+        if self.cells.len() <= w {
+            self.cells.resize(w + 1, 0);
+        }
+        let mask = (1 << self.bits) - 1;
+        self.cells[w] = (self.cells[w] & !(mask << shift)) | ((c as u64) << shift);
+    }
+
+    // A mask with the lowest bit of every cell in a word set.
+    fn low_bits(&self) -> u64 {
+        (0..self.per_word()).fold(0, |acc, k| acc | 1 << (self.bits * k))
+    }
+
+    // Folds each cell of `word` down into its lowest bit, so that bit is set exactly when the cell
+    // is alive. The other bits of each cell are left as junk.
+    fn fold(&self, mut word: u64) -> u64 {
+        let mut shift = 1;
+        while shift < self.bits {
+            word |= word >> shift;
+            shift <<= 1;
+        }
+        word
+    }
+
+    // The `n` (at most 64) bits starting at bit `start` of the packed cells, with zeros before
+    // the first cell and after the last.
+    fn bits_at(&self, start: i64, n: usize) -> u64 {
+        if start < 0 {
+            let skip = (-start) as usize;
+            return if skip >= n {
+                0
+            } else {
+                self.bits_at(0, n - skip) << skip
+            };
+        }
+        let (w, off) = (start as usize / 64, start as usize % 64);
+        let word = |w: usize| self.cells.get(w).cloned().unwrap_or(0);
+        let mut bits = word(w) >> off;
+        if off > 0 {
+            bits |= word(w + 1) << (64 - off);
+        }
+        if n < 64 {
+            bits &= (1 << n) - 1;
+        }
+        bits
+    }
+
     fn step(&mut self) {
-        let mut next = HashSet::new();
-        let lo = self.alive.iter().min().unwrap_or(&0);
-        let hi = self.alive.iter().max().unwrap_or(&0);
-        for i in (lo - 4)..=(hi + 4) {
-            let neighborhood: Vec<bool> =
-                (i - 2..=i + 2).map(|j| self.alive.contains(&j)).collect();
-            if self.rules.contains(&neighborhood) {
-                next.insert(i);
+        self.cells = match self.rule.chunks {
+            Some(ref chunks) => self.step_chunks(chunks),
+            None => self.step_cells(),
+        };
+        self.origin -= self.rule.radius as i64;
+        self.trim();
+    }
+
+    // Steps `chunk` cells at a time, with one lookup for each.
+    fn step_chunks(&self, chunks: &Chunks) -> Vec<u64> {
+        let (bits, per_word, radius) = (self.bits, self.per_word(), self.rule.radius);
+        let padding = (2 * radius).div_ceil(per_word);
+        let window = (chunks.chunk + 2 * radius) * bits;
+        // New cell `i` is centered on old cell `i - radius`, so it depends on old cells
+        // `i - 2 * radius` through `i`.
+        (0..self.cells.len() + padding)
+            .map(|w| {
+                (0..per_word).step_by(chunks.chunk).fold(0, |out, k| {
+                    let first = (w * per_word + k) as i64 - 2 * radius as i64;
+                    let idx = self.bits_at(first * bits as i64, window) as usize;
+                    out | u64::from(chunks.table[idx]) << (bits * k)
+                })
+            })
+            .collect()
+    }
+
+    // Steps one cell at a time, for rules that chunks don't work for.
+    fn step_cells(&self) -> Vec<u64> {
+        let rule = &self.rule;
+        let (bits, per_word) = (self.bits, self.per_word());
+        let mask = (1 << bits) - 1;
+        let (states, size) = (rule.states as usize, rule.table.len());
+        // When every bit pattern of a cell is a state, a neighborhood's index is just its cells'
+        // bits side by side, so the window can slide along with shifts instead of arithmetic.
+        let packed = states == 1 << bits;
+
+        // Every cell that could be alive next is within `radius` cells of a live cell now, so a
+        // few empty words on the end are enough.
+        let padding = (2 * rule.radius).div_ceil(per_word);
+        let mut next = Vec::with_capacity(self.cells.len() + padding);
+        let (mut out, mut filled) = (0, 0);
+        let mut idx = 0;
+        for &word in self.cells.iter().chain(iter::repeat_n(&0, padding)) {
+            for k in 0..per_word {
+                // `idx` is the neighborhood centered on the cell `radius` cells back from here.
+                let c = ((word >> (bits * k)) & mask) as usize;
+                idx = if packed {
+                    ((idx << bits) | c) & (size - 1)
+                } else {
+                    (idx * states + c) % size
+                };
+                out |= u64::from(rule.table[idx]) << (bits * filled);
+                filled += 1;
+                if filled == per_word {
+                    next.push(out);
+                    out = 0;
+                    filled = 0;
+                }
+            }
+        }
+        next
+    }
+
+    // Shifts the cells so that cell 0 is alive, and drops any trailing empty words.
+    fn trim(&mut self) {
+        while self.cells.last() == Some(&0) {
+            self.cells.pop();
+        }
+        let lo = match self.cells.iter().position(|&word| word != 0) {
+            Some(w) => 64 * w + self.cells[w].trailing_zeros() as usize,
+            None => return,
+        } / self.bits;
+        if lo > 0 {
+            let (words, bits) = (lo / self.per_word(), self.bits * (lo % self.per_word()));
+            self.cells.drain(..words);
+            if bits > 0 {
+                for w in 0..self.cells.len() {
+                    let carry = self.cells.get(w + 1).map_or(0, |&next| next << (64 - bits));
+                    self.cells[w] = (self.cells[w] >> bits) | carry;
+                }
+                if self.cells.last() == Some(&0) {
+                    self.cells.pop();
+                }
+            }
+            self.origin += lo as i64;
+        }
+    }
+
+    // The state of the cell at `position`.
+    fn cell(&self, position: i64) -> u8 {
+        if position < self.origin {
+            0
+        } else {
+            self.get((position - self.origin) as usize)
+        }
+    }
+
+    fn alive(&self) -> Vec<i64> {
+        let low = self.low_bits();
+        let mut alive = Vec::new();
+        for (w, &word) in self.cells.iter().enumerate() {
+            let mut live = self.fold(word) & low;
+            while live != 0 {
+                let i = w * self.per_word() + live.trailing_zeros() as usize / self.bits;
+                alive.push(self.origin + i as i64);
+                live &= live - 1;
+            }
+        }
+        alive
+    }
+
+    fn count(&self) -> usize {
+        let low = self.low_bits();
+        self.cells
+            .iter()
+            .map(|&word| (self.fold(word) & low).count_ones() as usize)
+            .sum()
+    }
+
+    fn sum(&self) -> i64 {
+        self.alive().into_iter().sum()
+    }
+
+    // Steps a copy of this state until its live pattern repeats up to a translation, recording
+    // the (sum, count) of live cells in every generation along the way.
+    fn find_cycle(&self, budget: u64) -> (Option<Cycle>, Vec<(i64, usize)>) {
+        let mut state = self.clone();
+        let mut seen: HashMap<Vec<u64>, (u64, i64)> = HashMap::new();
+        let mut history = Vec::new();
+        for generation in 0..=budget {
+            // Trimmed cells are already translation-invariant.
+            if let Some(&(start, prev_origin)) = seen.get(&state.cells) {
+                let cycle = Cycle {
+                    start,
+                    period: generation - start,
+                    offset: state.origin - prev_origin,
+                };
+                return (Some(cycle), history);
+            }
+            seen.insert(state.cells.clone(), (generation, state.origin));
+            history.push((state.sum(), state.count()));
+            state.step();
+        }
+        (None, history)
+    }
+
+    fn sum_after(&self, generations: u64) -> Result<i64, String> {
+        let (cycle, history) = self.find_cycle(CYCLE_BUDGET);
+        if let Some(&(sum, _)) = history.get(generations as usize) {
+            return Ok(sum);
+        }
+        let Cycle {
+            start,
+            period,
+            offset,
+        } = cycle.ok_or(format!("no cycle found within {} steps", CYCLE_BUDGET))?;
+        let laps = ((generations - start) / period) as i64;
+        let (sum, count) = history[(start + (generations - start) % period) as usize];
+        Ok(sum + laps * offset * count as i64)
+    }
+}
+
+lazy_static! {
+    // Example: initial state: #..#.#..##......###...###
+    static ref INITIAL: Regex = Regex::new(r"^initial state: (?P<cells>[.#]*)$").unwrap();
+    // Example: ...## => #
+    static ref RULE: Regex = Regex::new(r"^(?P<neighborhood>[.#]+) => (?P<next>[.#])$").unwrap();
+}
+impl FromStr for State {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut lines = s.lines().enumerate();
+        let initial = lines
+            .next()
+            .and_then(|(_, line)| INITIAL.captures(line))
+            .ok_or_else(|| String::from("line 1: expected an initial state"))?;
+        let alive = initial["cells"].bytes().enumerate().filter_map(|(i, b)| {
+            if b == b'#' {
+                Some(i as i64)
+            } else {
+                None
+            }
+        });
+
+        let mut rules: HashMap<Vec<bool>, bool> = HashMap::new();
+        for (i, line) in lines {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let cap = RULE.captures(line).ok_or(format!(
+                "line {}: could not parse rule {:?}",
+                i + 1,
+                line
+            ))?;
+            let neighborhood: Vec<bool> = cap["neighborhood"].bytes().map(|b| b == b'#').collect();
+            if rules.insert(neighborhood, &cap["next"] == "#").is_some() {
+                return Err(format!(
+                    "line {}: duplicate rule for {}",
+                    i + 1,
+                    &cap["neighborhood"]
+                ));
             }
         }
-        self.alive = next;
+
+        // The neighborhood width determines the radius, so every rule has to agree on it.
+        let width = rules.keys().map(|n| n.len()).max().unwrap_or(5);
+        if width % 2 == 0 || rules.keys().any(|n| n.len() != width) {
+            return Err(String::from(
+                "rules must all cover the same odd number of cells",
+            ));
+        }
+        let live = rules
+            .into_iter()
+            .filter_map(|(neighborhood, next)| if next { Some(neighborhood) } else { None });
+        let rule = Rule::from_neighborhoods(width / 2, live)?;
+        Ok(State::new(rule, alive))
+    }
+}
+
+pub(crate) struct Day12;
+
+impl Puzzle for Day12 {
+    type Input = State;
+
+    fn parse(s: &str) -> Result<State, String> {
+        s.parse()
+    }
+
+    fn part1(state: &State) -> Result<String, String> {
+        state.sum_after(20).map(|sum| sum.to_string())
+    }
+
+    fn part2(state: &State) -> Result<String, String> {
+        state.sum_after(50_000_000_000).map(|sum| sum.to_string())
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use lazy_static::lazy_static;
+    use crate::runner;
+    use std::collections::HashSet;
+    use std::fs;
 
     lazy_static! {
-        static ref INIT: Vec<u8> = b"##.#.#.##..#....######..#..#...#.#..#.#.#..###.#.#.#..#..###.##.#..#.##.##.#.####..##...##..#..##.#.".to_vec();
-        static ref RULES: Vec<Vec<u8>> = vec![
-            b"...##",
-            b".#...",
-            b".#..#",
-            b".#.##",
-            b".###.",
-            b".####",
-            b"#..#.",
-            b"#..##",
-            b"#.#.#",
-            b"#.##.",
-            b"##...",
-            b"##..#",
-            b"##.##",
-            b"###.."
-        ].into_iter().map(|s| s.to_vec()).collect();
-        static ref INPUT: State = State {
-            alive: INIT
-                .iter()
-                .enumerate()
-                .filter_map(|(i, &b)| if b == b'#' { Some(i as i32) } else { None })
-                .collect(),
-            rules: RULES.iter().map(|r| r.iter().map(|&b| b == b'#').collect()).collect()
-        };
+        static ref INPUT: State = fs::read_to_string("data/day12/input")
+            .expect("read input file")
+            .parse()
+            .expect("parse input");
+        static ref RULES: HashSet<Vec<bool>> = fs::read_to_string("data/day12/input")
+            .expect("read input file")
+            .lines()
+            .filter(|line| line.ends_with("=> #"))
+            .map(|line| line.bytes().take(5).map(|b| b == b'#').collect())
+            .collect();
     }
 
+    const EXAMPLE: &str = "initial state: #..#.#..##......###...###
+
+...## => #
+..#.. => #
+.#... => #
+.#.#. => #
+.#.## => #
+.##.. => #
+.#### => #
+#.#.# => #
+#.### => #
+##.#. => #
+##.## => #
+###.. => #
+###.# => #
+####. => #
+";
+
     #[test]
-    fn part1() {
+    fn smoke() {
+        let mut state: State = EXAMPLE.parse().unwrap();
+        for _ in 0..20 {
+            state.step();
+        }
+        assert_eq!(state.sum(), 325);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            "...## => #".parse::<State>().err(),
+            Some(String::from("line 1: expected an initial state"))
+        );
+        assert_eq!(
+            "initial state: #.#\n\n...## => #\n...## -> #"
+                .parse::<State>()
+                .err(),
+            Some(String::from("line 4: could not parse rule \"...## -> #\""))
+        );
+        assert_eq!(
+            "initial state: #.#\n\n...## => #\n...## => ."
+                .parse::<State>()
+                .err(),
+            Some(String::from("line 4: duplicate rule for ...##"))
+        );
+    }
+
+    // The straightforward set-based step, for checking the bitset engine against.
+    fn naive_step(alive: &HashSet<i64>, rules: &HashSet<Vec<bool>>) -> HashSet<i64> {
+        let lo = alive.iter().min().unwrap_or(&0);
+        let hi = alive.iter().max().unwrap_or(&0);
+        ((lo - 4)..=(hi + 4))
+            .filter(|&i| {
+                let neighborhood: Vec<bool> = (i - 2..=i + 2).map(|j| alive.contains(&j)).collect();
+                rules.contains(&neighborhood)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn matches_naive() {
+        let mut alive: HashSet<i64> = INPUT.alive().into_iter().collect();
         let mut state = INPUT.clone();
+        for _ in 0..200 {
+            alive = naive_step(&alive, &RULES);
+            state.step();
+            let mut expected: Vec<i64> = alive.iter().cloned().collect();
+            expected.sort();
+            assert_eq!(state.alive(), expected);
+        }
+    }
+
+    #[test]
+    fn negative_indices() {
+        // Every live cell spawns a live cell two to its left, and dies.
+        let rule = Rule::from_neighborhoods(2, vec![vec![false, false, false, false, true]]);
+        let mut state = State::new(rule.unwrap(), vec![0, 70]);
+        for _ in 0..100 {
+            state.step();
+        }
+        assert_eq!(state.alive(), vec![-200, -130]);
+    }
+
+    #[test]
+    fn part1() {
+        runner::check_answer::<Day12>(12, 1);
+    }
+
+    #[test]
+    fn glider() {
+        // A single cell that moves one step to the right every generation.
+        let rule = Rule::from_neighborhoods(2, vec![vec![false, true, false, false, false]]);
+        let state = State::new(rule.unwrap(), vec![0]);
+        let (cycle, _) = state.find_cycle(10);
+        assert_eq!(
+            cycle,
+            Some(Cycle {
+                start: 0,
+                period: 1,
+                offset: 1
+            })
+        );
+        assert_eq!(state.sum_after(1_000_000), Ok(1_000_000));
+    }
+
+    #[test]
+    fn no_cycle() {
+        // The live region grows by one cell on each side every generation.
+        let rules = (1..32u32).map(|n| (0..5).map(|bit| n & (1 << bit) != 0).collect());
+        let state = State::new(Rule::from_neighborhoods(2, rules).unwrap(), vec![0]);
+        assert_eq!(state.find_cycle(50).0, None);
+        assert_eq!(state.sum_after(10), Ok(0));
+        assert!(state.sum_after(CYCLE_BUDGET + 1).is_err());
+    }
+
+    #[test]
+    fn elementary() {
+        // The center column of rule 30, starting from a single live cell.
+        let mut state = State::new(Rule::elementary(30).unwrap(), vec![0]);
+        let mut center = Vec::new();
         for _ in 0..20 {
+            center.push(state.cell(0));
+            state.step();
+        }
+        assert_eq!(
+            center,
+            vec![1, 1, 0, 1, 1, 1, 0, 0, 1, 1, 0, 0, 0, 1, 0, 1, 1, 0, 0, 1]
+        );
+
+        // Rule 110 grows to the left.
+        let mut state = State::new(Rule::elementary(110).unwrap(), vec![0]);
+        for _ in 0..3 {
             state.step();
         }
-        assert_eq!(state.alive.iter().sum::<i32>(), 2140);
+        assert_eq!(state.alive(), vec![-3, -2, 0]);
+
+        // Odd rules turn dead neighborhoods live.
+        assert!(Rule::elementary(1).is_err());
+    }
+
+    #[test]
+    fn multiple_states() {
+        // Each cell becomes the sum of its neighbors mod 3, which traces out Pascal's triangle.
+        let table = (0..27).map(|idx| ((idx / 9 + idx % 3) % 3) as u8).collect();
+        let mut state = State::new(Rule::new(1, 3, table).unwrap(), vec![0]);
+        let mut rows = Vec::new();
+        for _ in 0..4 {
+            state.step();
+            rows.push((-4..=4).map(|i| state.cell(i)).collect::<Vec<_>>());
+        }
+        assert_eq!(
+            rows,
+            vec![
+                vec![0, 0, 0, 1, 0, 1, 0, 0, 0],
+                vec![0, 0, 1, 0, 2, 0, 1, 0, 0],
+                vec![0, 1, 0, 0, 0, 0, 0, 1, 0],
+                vec![1, 0, 1, 0, 0, 0, 1, 0, 1],
+            ]
+        );
+        assert!(Rule::new(1, 3, vec![0; 8]).is_err());
+        assert!(Rule::new(1, 3, vec![3; 27]).is_err());
+    }
+
+    // The next state of the cell at `position`, looked up one cell at a time.
+    fn naive_cell(state: &State, position: i64) -> u8 {
+        let Rule {
+            radius,
+            states,
+            ref table,
+            ..
+        } = state.rule;
+        let r = radius as i64;
+        let idx = (position - r..=position + r)
+            .fold(0, |acc, j| acc * states as usize + state.cell(j) as usize);
+        table[idx]
+    }
+
+    #[test]
+    fn matches_naive_multiple_states() {
+        // 4 states fill their bits exactly, so they take the shifting path; 3 states don't.
+        for &states in &[3u8, 4] {
+            let size = (states as usize).pow(5);
+            let table = (0..size)
+                .map(|idx| {
+                    if idx == 0 {
+                        0
+                    } else {
+                        (idx * 7 % 11) as u8 % states
+                    }
+                })
+                .collect();
+            let rule = Rule::new(2, states, table).unwrap();
+            let cells = (0..40).map(|i| (i * 3 - 50, (i % states as i64) as u8));
+            let mut state = State::with_cells(rule, cells);
+            for _ in 0..50 {
+                let lo = state.origin - 2;
+                let hi = state.origin + (state.per_word() * state.cells.len()) as i64 + 2;
+                let expected: Vec<(i64, u8)> = (lo..hi)
+                    .map(|i| (i, naive_cell(&state, i)))
+                    .filter(|&(_, c)| c != 0)
+                    .collect();
+                state.step();
+                let actual: Vec<(i64, u8)> = state
+                    .alive()
+                    .into_iter()
+                    .map(|i| (i, state.cell(i)))
+                    .collect();
+                assert_eq!(actual, expected);
+                assert_eq!(state.count(), expected.len());
+            }
+        }
+    }
+
+    #[test]
+    fn wide_radius() {
+        // A glider that moves three cells to the right every generation.
+        let mut live = vec![false; 7];
+        live[0] = true;
+        let state = State::new(Rule::from_neighborhoods(3, vec![live]).unwrap(), vec![5]);
+        assert_eq!(
+            state.find_cycle(10).0,
+            Some(Cycle {
+                start: 0,
+                period: 1,
+                offset: 3
+            })
+        );
+        assert_eq!(state.sum_after(100), Ok(305));
+
+        // The parser picks the radius up from the rules.
+        let state: State = "initial state: #\n\n#...... => #".parse().unwrap();
+        assert_eq!(state.sum_after(2), Ok(6));
+        assert!("initial state: #\n\n#.... => #\n#.. => #"
+            .parse::<State>()
+            .is_err());
     }
 
     #[test]
     fn part2() {
+        runner::check_answer::<Day12>(12, 2);
+    }
+
+    #[test]
+    fn direct_simulation() {
+        let generations = 1_000_000;
         let mut state = INPUT.clone();
+        for _ in 0..generations {
+            state.step();
+        }
+        assert_eq!(Ok(state.sum()), INPUT.sum_after(generations));
+    }
 
-        let warmup = 100;
-        for _ in 0..warmup {
+    #[test]
+    fn chunks_match_cells() {
+        let mut state = INPUT.clone();
+        assert_eq!(state.rule.chunks.as_ref().map(|c| c.chunk), Some(8));
+        for _ in 0..200 {
+            assert_eq!(
+                state.step_chunks(state.rule.chunks.as_ref().unwrap()),
+                state.step_cells()
+            );
             state.step();
         }
-        // Hope and pray that this exhibits some simple linear growth.
-        let v0 = state.alive.iter().sum::<i32>() as i64;
-        state.step();
-        let v1 = state.alive.iter().sum::<i32>() as i64;
-        let v_50b = v0 + (50_000_000_000 - warmup) * (v1 - v0);
-        assert_eq!(v_50b, 1900000000384);
     }
 }
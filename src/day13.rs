@@ -0,0 +1,357 @@
+use crate::geometry::Point;
+use crate::runner::Puzzle;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+// How many ticks `first_crash` and `last_cart` will simulate before giving up.
+const TICK_LIMIT: usize = 1_000_000;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+impl Direction {
+    fn turn_left(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Left => Direction::Down,
+            Direction::Down => Direction::Right,
+            Direction::Right => Direction::Up,
+        }
+    }
+    fn turn_right(self) -> Direction {
+        self.turn_left().turn_left().turn_left()
+    }
+    fn advance(self, p: Point) -> Point {
+        match self {
+            Direction::Up => Point(p.0, p.1 - 1),
+            Direction::Down => Point(p.0, p.1 + 1),
+            Direction::Left => Point(p.0 - 1, p.1),
+            Direction::Right => Point(p.0 + 1, p.1),
+        }
+    }
+}
+
+// What a cart does at its next intersection. Carts cycle through left, straight, right.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Turn {
+    Left,
+    Straight,
+    Right,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Cart {
+    pos: Point,
+    dir: Direction,
+    next_turn: Turn,
+}
+impl Cart {
+    fn advance(&mut self, track: u8) {
+        self.pos = self.dir.advance(self.pos);
+        self.dir = match (track, self.dir) {
+            (b'/', Direction::Up) | (b'/', Direction::Down) => self.dir.turn_right(),
+            (b'/', _) => self.dir.turn_left(),
+            (b'\\', Direction::Up) | (b'\\', Direction::Down) => self.dir.turn_left(),
+            (b'\\', _) => self.dir.turn_right(),
+            (b'+', _) => {
+                let (dir, next_turn) = match self.next_turn {
+                    Turn::Left => (self.dir.turn_left(), Turn::Straight),
+                    Turn::Straight => (self.dir, Turn::Right),
+                    Turn::Right => (self.dir.turn_right(), Turn::Left),
+                };
+                self.next_turn = next_turn;
+                dir
+            }
+            _ => self.dir,
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Mine {
+    // The track underneath each cell, with carts replaced by the straight track they sit on.
+    tracks: Vec<Vec<u8>>,
+    carts: Vec<Cart>,
+}
+
+impl FromStr for Mine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let rows: Vec<&[u8]> = s.lines().map(str::as_bytes).collect();
+        let mut tracks = Vec::new();
+        let mut carts = Vec::new();
+        for (y, line) in s.lines().enumerate() {
+            let mut row = Vec::new();
+            for (x, b) in line.bytes().enumerate() {
+                let (track, dir) = match b {
+                    b'^' => (b'|', Some(Direction::Up)),
+                    b'v' => (b'|', Some(Direction::Down)),
+                    b'<' => (b'-', Some(Direction::Left)),
+                    b'>' => (b'-', Some(Direction::Right)),
+                    b' ' | b'|' | b'-' | b'/' | b'\\' | b'+' => (b, None),
+                    _ => return Err(format!("unexpected {:?} at {},{}", b as char, x, y)),
+                };
+                if let Some(dir) = dir {
+                    check_straight(&rows, x, y, dir)?;
+                    carts.push(Cart {
+                        pos: Point(x as i32, y as i32),
+                        dir,
+                        next_turn: Turn::Left,
+                    });
+                }
+                row.push(track);
+            }
+            tracks.push(row);
+        }
+        Ok(Mine { tracks, carts })
+    }
+}
+
+// Carts always start out on straight track. The track has to carry on past the cart on both sides,
+// and no track can join it from the other two, or the cart would be on a curve or an intersection
+// and there'd be no telling which.
+fn check_straight(rows: &[&[u8]], x: usize, y: usize, dir: Direction) -> Result<(), String> {
+    let at = |dx: i32, dy: i32| {
+        let (x, y) = (x as i32 + dx, y as i32 + dy);
+        if x < 0 || y < 0 {
+            return b' ';
+        }
+        rows.get(y as usize)
+            .and_then(|row| row.get(x as usize))
+            .cloned()
+            .unwrap_or(b' ')
+    };
+    let (along, across, joins_along, joins_across): (_, _, &[u8], &[u8]) = match dir {
+        Direction::Left | Direction::Right => (
+            [at(-1, 0), at(1, 0)],
+            [at(0, -1), at(0, 1)],
+            b"-+/\\<>",
+            b"|+^v",
+        ),
+        Direction::Up | Direction::Down => (
+            [at(0, -1), at(0, 1)],
+            [at(-1, 0), at(1, 0)],
+            b"|+/\\^v",
+            b"-+<>",
+        ),
+    };
+    if along.iter().all(|b| joins_along.contains(b))
+        && !across.iter().any(|b| joins_across.contains(b))
+    {
+        Ok(())
+    } else {
+        Err(format!("cart at {},{} is not on straight track", x, y))
+    }
+}
+
+// Spots the carts getting back into a position they've been in before, using Brent's algorithm so
+// that only one earlier position has to be kept. Between crashes the carts just follow the
+// tracks, so from then on they go round in the same circles forever.
+struct Repeats {
+    saved: Vec<Cart>,
+    power: usize,
+    since: usize,
+    ticks: usize,
+}
+
+impl Repeats {
+    fn new(carts: &[Cart]) -> Repeats {
+        Repeats {
+            saved: carts.to_vec(),
+            power: 1,
+            since: 0,
+            ticks: 0,
+        }
+    }
+
+    // Whether it's time to give up: the carts are repeating themselves, or have been going for
+    // `TICK_LIMIT` ticks.
+    fn seen(&mut self, carts: &[Cart]) -> bool {
+        self.ticks += 1;
+        if self.ticks >= TICK_LIMIT || carts == &self.saved[..] {
+            return true;
+        }
+        self.since += 1;
+        if self.since == self.power {
+            self.saved = carts.to_vec();
+            self.power *= 2;
+            self.since = 0;
+        }
+        false
+    }
+}
+
+impl Mine {
+    fn track(&self, p: Point) -> u8 {
+        self.tracks
+            .get(p.1 as usize)
+            .and_then(|row| row.get(p.0 as usize))
+            .cloned()
+            .unwrap_or(b' ')
+    }
+
+    // Moves every cart once, in reading order, and returns the locations of any crashes. Carts
+    // that crash are removed immediately, so they can't be hit again later in the same tick. The
+    // carts are kept in reading order in between ticks (the parser finds them that way too).
+    fn tick(&mut self) -> Vec<Point> {
+        let mut occupied: HashMap<Point, usize> = self
+            .carts
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (c.pos, i))
+            .collect();
+        let mut crashed = vec![false; self.carts.len()];
+        let mut crashes = Vec::new();
+        for i in 0..self.carts.len() {
+            if crashed[i] {
+                continue;
+            }
+            occupied.remove(&self.carts[i].pos);
+            let next = self.carts[i].dir.advance(self.carts[i].pos);
+            let track = self.track(next);
+            self.carts[i].advance(track);
+            if let Some(j) = occupied.remove(&next) {
+                crashed[i] = true;
+                crashed[j] = true;
+                crashes.push(next);
+            } else {
+                occupied.insert(next, i);
+            }
+        }
+
+        let mut i = 0;
+        self.carts.retain(|_| {
+            i += 1;
+            !crashed[i - 1]
+        });
+        self.carts.sort_by_key(|c| (c.pos.1, c.pos.0));
+        crashes
+    }
+
+    // Where the first crash happens, if the carts ever crash at all.
+    fn first_crash(&mut self) -> Option<Point> {
+        let mut repeats = Repeats::new(&self.carts);
+        while !self.carts.is_empty() {
+            if let Some(&p) = self.tick().first() {
+                return Some(p);
+            }
+            if repeats.seen(&self.carts) {
+                return None;
+            }
+        }
+        None
+    }
+
+    // The position of the last cart standing, once every other cart has crashed. There isn't one
+    // if the last few carts never run into each other.
+    fn last_cart(&mut self) -> Option<Point> {
+        let mut repeats = Repeats::new(&self.carts);
+        while self.carts.len() > 1 {
+            self.tick();
+            if repeats.seen(&self.carts) {
+                return None;
+            }
+        }
+        self.carts.first().map(|c| c.pos)
+    }
+}
+
+pub(crate) struct Day13;
+
+impl Puzzle for Day13 {
+    type Input = Mine;
+
+    fn parse(s: &str) -> Result<Mine, String> {
+        s.parse()
+    }
+
+    fn part1(mine: &Mine) -> Result<String, String> {
+        let p = mine
+            .clone()
+            .first_crash()
+            .ok_or_else(|| String::from("the carts never crash"))?;
+        Ok(format!("{},{}", p.0, p.1))
+    }
+
+    fn part2(mine: &Mine) -> Result<String, String> {
+        let p = mine
+            .clone()
+            .last_cart()
+            .ok_or_else(|| String::from("the carts never get down to one"))?;
+        Ok(format!("{},{}", p.0, p.1))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn straight_line() {
+        let mut mine: Mine = "|\nv\n|\n|\n|\n^\n|".parse().unwrap();
+        assert_eq!(mine.first_crash(), Some(Point(0, 3)));
+    }
+
+    #[test]
+    fn part1() {
+        let mut mine: Mine = r"/->-\
+|   |  /----\
+| /-+--+-\  |
+| | |  | v  |
+\-+-/  \-+--/
+  \------/"
+            .parse()
+            .unwrap();
+        assert_eq!(mine.first_crash(), Some(Point(7, 3)));
+    }
+
+    #[test]
+    fn part2() {
+        let mut mine: Mine = r"/>-<\
+|   |
+| /<+-\
+| | | v
+\>+</ |
+  |   ^
+  \<->/"
+            .parse()
+            .unwrap();
+        assert_eq!(mine.last_cart(), Some(Point(6, 4)));
+    }
+
+    #[test]
+    fn parse_error() {
+        assert_eq!(
+            "|\nx".parse::<Mine>().err(),
+            Some(String::from("unexpected 'x' at 0,1"))
+        );
+        // A cart at an intersection, and one on a corner.
+        assert_eq!(
+            "  |\n-->-\n  |".parse::<Mine>().err(),
+            Some(String::from("cart at 2,1 is not on straight track"))
+        );
+        assert_eq!(
+            "/-\\\n| |\n\\-^".parse::<Mine>().err(),
+            Some(String::from("cart at 2,2 is not on straight track"))
+        );
+    }
+
+    #[test]
+    fn no_crash() {
+        // Two carts on loops of their own.
+        let separate = "/>\\ /<\\\n| | | |\n\\-/ \\-/";
+        let mut mine: Mine = separate.parse().unwrap();
+        assert_eq!(mine.first_crash(), None);
+        let mut mine: Mine = separate.parse().unwrap();
+        assert_eq!(mine.last_cart(), None);
+
+        // Two carts chasing each other round the same loop at the same speed.
+        let mut mine: Mine = "/>-\\\n| |\n| |\n\\<-/".parse().unwrap();
+        assert_eq!(mine.first_crash(), None);
+    }
+}
@@ -0,0 +1,121 @@
+use crate::runner::Puzzle;
+
+struct Scoreboard {
+    scores: Vec<u8>,
+    elves: [usize; 2],
+}
+
+impl Scoreboard {
+    fn new() -> Scoreboard {
+        Scoreboard {
+            scores: vec![3, 7],
+            elves: [0, 1],
+        }
+    }
+
+    // Appends the digits of the elves' combined score, then moves each elf forward.
+    fn step(&mut self) {
+        let sum = self.scores[self.elves[0]] + self.scores[self.elves[1]];
+        if sum >= 10 {
+            self.scores.push(sum / 10);
+        }
+        self.scores.push(sum % 10);
+        let len = self.scores.len();
+        for elf in self.elves.iter_mut() {
+            *elf = (*elf + 1 + self.scores[*elf] as usize) % len;
+        }
+    }
+}
+
+fn ten_after(num_recipes: usize) -> String {
+    let mut board = Scoreboard::new();
+    board.scores.reserve(num_recipes + 11);
+    while board.scores.len() < num_recipes + 10 {
+        board.step();
+    }
+    board.scores[num_recipes..num_recipes + 10]
+        .iter()
+        .map(|&d| (b'0' + d) as char)
+        .collect()
+}
+
+fn recipes_before(digits: &str) -> usize {
+    let target: Vec<u8> = digits.bytes().map(|b| b - b'0').collect();
+    let mut board = Scoreboard::new();
+    // Every window ending before `checked` has already been compared against the target.
+    let mut checked = 0;
+    loop {
+        // A step may append two recipes, so the match can end on either of them.
+        while checked < board.scores.len() {
+            checked += 1;
+            if checked >= target.len()
+                && board.scores[checked - target.len()..checked] == target[..]
+            {
+                return checked - target.len();
+            }
+        }
+        board.step();
+    }
+}
+
+pub(crate) struct Day14;
+
+impl Puzzle for Day14 {
+    type Input = String;
+
+    fn parse(s: &str) -> Result<String, String> {
+        let digits = s.trim();
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(format!("expected a number, found {:?}", digits));
+        }
+        Ok(String::from(digits))
+    }
+
+    fn part1(digits: &String) -> Result<String, String> {
+        let num_recipes = digits
+            .parse()
+            .map_err(|_| format!("{} is too big", digits))?;
+        Ok(ten_after(num_recipes))
+    }
+
+    fn part2(digits: &String) -> Result<String, String> {
+        Ok(recipes_before(digits).to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::runner;
+
+    #[test]
+    fn smoke() {
+        assert_eq!(ten_after(5), "0124515891");
+        assert_eq!(ten_after(9), "5158916779");
+        assert_eq!(ten_after(18), "9251071085");
+        assert_eq!(ten_after(2018), "5941429882");
+
+        assert_eq!(recipes_before("01245"), 5);
+        assert_eq!(recipes_before("51589"), 9);
+        assert_eq!(recipes_before("92510"), 18);
+        assert_eq!(recipes_before("59414"), 2018);
+    }
+
+    #[test]
+    fn second_of_two_digits() {
+        // After one step the board is 3, 7, 1, 0: "1" and "0" were appended together, so a
+        // match on "371" ends one before the end.
+        assert_eq!(recipes_before("371"), 0);
+        assert_eq!(recipes_before("710"), 1);
+    }
+
+    #[test]
+    fn part1() {
+        runner::check_answer::<Day14>(14, 1);
+    }
+
+    #[test]
+    fn part2() {
+        runner::check_answer::<Day14>(14, 2);
+    }
+}
@@ -0,0 +1,431 @@
+use crate::geometry::{Coordinates, Point};
+use crate::runner::Puzzle;
+use crate::search;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+// Units take turns, pick targets and break ties in reading order: top to bottom, then left to
+// right.
+fn reading_order(p: &Point) -> (i32, i32) {
+    (p.1, p.0)
+}
+
+// Breadth-first distances from `start` to every point reachable through points where `open`
+// holds. The start itself is always included, at distance 0.
+fn distances<F>(start: Point, open: F) -> HashMap<Point, usize>
+where
+    F: Fn(Point) -> bool,
+{
+    let neighbors = |cur: &Point| {
+        cur.neighbors()
+            .into_iter()
+            .filter(|&n| open(n))
+            .collect::<Vec<_>>()
+    };
+    search::bfs(Some(start), neighbors, |_| false, None).into_distances()
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Race {
+    Elf,
+    Goblin,
+}
+
+#[derive(Debug, Clone)]
+struct Unit {
+    race: Race,
+    pos: Point,
+    hp: i32,
+    power: i32,
+}
+
+#[derive(Clone)]
+pub(crate) struct Battle {
+    walls: HashSet<Point>,
+    units: Vec<Unit>,
+    width: i32,
+    height: i32,
+    rounds: usize,
+}
+
+impl FromStr for Battle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut walls = HashSet::new();
+        let mut units = Vec::new();
+        let (mut width, mut height) = (0, 0);
+        for (y, line) in s.lines().enumerate() {
+            for (x, b) in line.bytes().enumerate() {
+                let pos = Point(x as i32, y as i32);
+                let race = match b {
+                    b'#' => {
+                        walls.insert(pos);
+                        None
+                    }
+                    b'.' => None,
+                    b'E' => Some(Race::Elf),
+                    b'G' => Some(Race::Goblin),
+                    _ => return Err(format!("unexpected {:?} at {},{}", b as char, x, y)),
+                };
+                if let Some(race) = race {
+                    units.push(Unit {
+                        race,
+                        pos,
+                        hp: 200,
+                        power: 3,
+                    });
+                }
+                width = width.max(x as i32 + 1);
+            }
+            height = y as i32 + 1;
+        }
+        Ok(Battle {
+            walls,
+            units,
+            width,
+            height,
+            rounds: 0,
+        })
+    }
+}
+
+impl fmt::Display for Battle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let units: HashMap<Point, Race> = self.units.iter().map(|u| (u.pos, u.race)).collect();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let p = Point(x, y);
+                let c = match units.get(&p) {
+                    Some(Race::Elf) => 'E',
+                    Some(Race::Goblin) => 'G',
+                    None if self.walls.contains(&p) => '#',
+                    None => '.',
+                };
+                write!(f, "{}", c)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Battle {
+    fn with_elf_power(&self, power: i32) -> Battle {
+        let mut battle = self.clone();
+        for unit in battle.units.iter_mut() {
+            if unit.race == Race::Elf {
+                unit.power = power;
+            }
+        }
+        battle
+    }
+
+    fn count(&self, race: Race) -> usize {
+        self.units.iter().filter(|u| u.race == race).count()
+    }
+
+    // The square unit `i` should step onto this turn, if any.
+    fn next_step(&self, i: usize) -> Option<Point> {
+        let unit = &self.units[i];
+        let occupied: HashSet<Point> = self
+            .units
+            .iter()
+            .filter(|u| u.hp > 0)
+            .map(|u| u.pos)
+            .collect();
+        let open = |p: Point| !self.walls.contains(&p) && !occupied.contains(&p);
+
+        // Every open square next to an enemy is a candidate destination.
+        let in_range: HashSet<Point> = self
+            .units
+            .iter()
+            .filter(|u| u.hp > 0 && u.race != unit.race)
+            .flat_map(|u| u.pos.neighbors())
+            .collect();
+        if in_range.contains(&unit.pos) {
+            return None;
+        }
+
+        let from_unit = distances(unit.pos, open);
+        let target = *in_range
+            .iter()
+            .filter(|p| from_unit.contains_key(p))
+            .min_by_key(|p| (from_unit[p], reading_order(p)))?;
+
+        // Take the first step along a shortest path to the target.
+        let to_target = distances(target, open);
+        unit.pos
+            .neighbors()
+            .into_iter()
+            .filter(|p| to_target.contains_key(p))
+            .min_by_key(|p| (to_target[p], reading_order(p)))
+    }
+
+    // Runs one round of combat, and returns whether it completed. A round is cut short as soon
+    // as a unit starts its turn with no enemies left. A full round where nobody moves or attacks
+    // would repeat forever, so it's an error.
+    fn round(&mut self) -> Result<bool, String> {
+        self.units.sort_by_key(|u| reading_order(&u.pos));
+        let (mut complete, mut active) = (true, false);
+        for i in 0..self.units.len() {
+            if self.units[i].hp <= 0 {
+                continue;
+            }
+            let race = self.units[i].race;
+            if !self.units.iter().any(|u| u.hp > 0 && u.race != race) {
+                complete = false;
+                break;
+            }
+
+            if let Some(p) = self.next_step(i) {
+                self.units[i].pos = p;
+                active = true;
+            }
+
+            let pos = self.units[i].pos;
+            let target = self
+                .units
+                .iter()
+                .enumerate()
+                .filter(|(_, u)| u.hp > 0 && u.race != race && u.pos.manhattan(&pos) == 1)
+                .min_by_key(|(_, u)| (u.hp, reading_order(&u.pos)))
+                .map(|(j, _)| j);
+            if let Some(j) = target {
+                self.units[j].hp -= self.units[i].power;
+                active = true;
+            }
+        }
+        if complete && !active {
+            return Err(format!(
+                "stalemate after {} rounds: no unit can reach an enemy",
+                self.rounds
+            ));
+        }
+        self.units.retain(|u| u.hp > 0);
+        if complete {
+            self.rounds += 1;
+        }
+        Ok(complete)
+    }
+
+    fn hit_points(&self) -> i32 {
+        self.units.iter().map(|u| u.hp).sum()
+    }
+
+    fn outcome(&self) -> usize {
+        self.rounds * self.hit_points() as usize
+    }
+
+    fn fight(&mut self) -> Result<usize, String> {
+        while self.round()? {}
+        Ok(self.outcome())
+    }
+
+    // Fights until one side wins, but gives up as soon as any elf dies.
+    fn fight_flawlessly(&mut self) -> Result<Option<usize>, String> {
+        let elves = self.count(Race::Elf);
+        loop {
+            let complete = self.round()?;
+            if self.count(Race::Elf) < elves {
+                return Ok(None);
+            }
+            if !complete {
+                return Ok(Some(self.outcome()));
+            }
+        }
+    }
+
+    // The lowest elf attack power that wins without losing a single elf, and the outcome of
+    // that battle.
+    fn minimal_elf_power(&self) -> Result<(i32, usize), String> {
+        let mut power = 4;
+        loop {
+            if let Some(outcome) = self.with_elf_power(power).fight_flawlessly()? {
+                return Ok((power, outcome));
+            }
+            power += 1;
+        }
+    }
+}
+
+pub(crate) struct Day15;
+
+impl Puzzle for Day15 {
+    type Input = Battle;
+
+    fn parse(s: &str) -> Result<Battle, String> {
+        s.parse()
+    }
+
+    fn part1(battle: &Battle) -> Result<String, String> {
+        battle.clone().fight().map(|outcome| outcome.to_string())
+    }
+
+    fn part2(battle: &Battle) -> Result<String, String> {
+        battle
+            .minimal_elf_power()
+            .map(|(_, outcome)| outcome.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLES: [&str; 6] = [
+        "#######
+#.G...#
+#...EG#
+#.#.#G#
+#..G#E#
+#.....#
+#######",
+        "#######
+#G..#E#
+#E#E.E#
+#G.##.#
+#...#E#
+#...E.#
+#######",
+        "#######
+#E..EG#
+#.#G.E#
+#E.##E#
+#G..#.#
+#..E#.#
+#######",
+        "#######
+#E.G#.#
+#.#G..#
+#G.#.G#
+#G..#.#
+#...E.#
+#######",
+        "#######
+#.E...#
+#.#..G#
+#.###.#
+#E#G#G#
+#...#G#
+#######",
+        "#########
+#G......#
+#.E.#...#
+#..##..G#
+#...##..#
+#...#...#
+#.G...G.#
+#.....G.#
+#########",
+    ];
+
+    #[test]
+    fn movement() {
+        let mut battle: Battle = "#########
+#G..G..G#
+#.......#
+#.......#
+#G..E..G#
+#.......#
+#.......#
+#G..G..G#
+#########"
+            .parse()
+            .unwrap();
+        for _ in 0..3 {
+            assert_eq!(battle.round(), Ok(true));
+        }
+        assert_eq!(
+            battle.to_string(),
+            "#########
+#.......#
+#..GGG..#
+#..GEG..#
+#G..G...#
+#......G#
+#.......#
+#.......#
+#########
+"
+        );
+    }
+
+    #[test]
+    fn rounds() {
+        let mut battle: Battle = EXAMPLES[0].parse().unwrap();
+        assert_eq!(battle.fight(), Ok(27730));
+        assert_eq!(battle.rounds, 47);
+        assert_eq!(
+            battle.units.iter().map(|u| u.hp).collect::<Vec<_>>(),
+            vec![200, 131, 59, 200]
+        );
+        assert_eq!(
+            battle.to_string(),
+            "#######
+#G....#
+#.G...#
+#.#.#G#
+#...#.#
+#....G#
+#######
+"
+        );
+    }
+
+    #[test]
+    fn part1() {
+        let outcomes: Vec<usize> = EXAMPLES
+            .iter()
+            .map(|s| s.parse::<Battle>().unwrap().fight().unwrap())
+            .collect();
+        assert_eq!(outcomes, vec![27730, 36334, 39514, 27755, 28944, 18740]);
+    }
+
+    #[test]
+    fn part2() {
+        let results: Vec<(i32, usize)> = [0, 2, 3, 4, 5]
+            .iter()
+            .map(|&i| {
+                EXAMPLES[i]
+                    .parse::<Battle>()
+                    .unwrap()
+                    .minimal_elf_power()
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(
+            results,
+            vec![(15, 4988), (4, 31284), (15, 3478), (12, 6474), (34, 1140)]
+        );
+    }
+
+    #[test]
+    fn stalemate() {
+        let battle: Battle = "#######\n#E.#.G#\n#######".parse().unwrap();
+        let error = Some(String::from(
+            "stalemate after 0 rounds: no unit can reach an enemy",
+        ));
+        assert_eq!(battle.clone().fight().err(), error);
+        assert_eq!(battle.minimal_elf_power().err(), error);
+
+        // Units that are still fighting elsewhere keep the battle going.
+        let mut battle: Battle = "########\n#EG#E#G#\n########".parse().unwrap();
+        assert_eq!(battle.round(), Ok(true));
+        assert_eq!(
+            battle.fight().err(),
+            Some(String::from(
+                "stalemate after 67 rounds: no unit can reach an enemy"
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_error() {
+        assert_eq!(
+            "#.#\n#X#".parse::<Battle>().err(),
+            Some(String::from("unexpected 'X' at 1,1"))
+        );
+    }
+}
@@ -0,0 +1,373 @@
+use crate::runner::Puzzle;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub(crate) enum Opcode {
+    Addr,
+    Addi,
+    Mulr,
+    Muli,
+    Banr,
+    Bani,
+    Borr,
+    Bori,
+    Setr,
+    Seti,
+    Gtir,
+    Gtri,
+    Gtrr,
+    Eqir,
+    Eqri,
+    Eqrr,
+}
+
+pub(crate) const ALL: [Opcode; 16] = [
+    Opcode::Addr,
+    Opcode::Addi,
+    Opcode::Mulr,
+    Opcode::Muli,
+    Opcode::Banr,
+    Opcode::Bani,
+    Opcode::Borr,
+    Opcode::Bori,
+    Opcode::Setr,
+    Opcode::Seti,
+    Opcode::Gtir,
+    Opcode::Gtri,
+    Opcode::Gtrr,
+    Opcode::Eqir,
+    Opcode::Eqri,
+    Opcode::Eqrr,
+];
+
+// The mnemonic for each opcode, in the same order as `ALL`.
+const NAMES: [&str; 16] = [
+    "addr", "addi", "mulr", "muli", "banr", "bani", "borr", "bori", "setr", "seti", "gtir", "gtri",
+    "gtrr", "eqir", "eqri", "eqrr",
+];
+
+impl Opcode {
+    pub(crate) fn name(self) -> &'static str {
+        NAMES[self as usize]
+    }
+
+    // The value this instruction writes to its output register, or `None` if it reads from a
+    // register that doesn't exist.
+    pub(crate) fn eval(self, regs: &[usize], a: usize, b: usize) -> Option<usize> {
+        let r = |i: usize| regs.get(i).cloned();
+        let value = match self {
+            Opcode::Addr => r(a)? + r(b)?,
+            Opcode::Addi => r(a)? + b,
+            Opcode::Mulr => r(a)? * r(b)?,
+            Opcode::Muli => r(a)? * b,
+            Opcode::Banr => r(a)? & r(b)?,
+            Opcode::Bani => r(a)? & b,
+            Opcode::Borr => r(a)? | r(b)?,
+            Opcode::Bori => r(a)? | b,
+            Opcode::Setr => r(a)?,
+            Opcode::Seti => a,
+            Opcode::Gtir => (a > r(b)?) as usize,
+            Opcode::Gtri => (r(a)? > b) as usize,
+            Opcode::Gtrr => (r(a)? > r(b)?) as usize,
+            Opcode::Eqir => (a == r(b)?) as usize,
+            Opcode::Eqri => (r(a)? == b) as usize,
+            Opcode::Eqrr => (r(a)? == r(b)?) as usize,
+        };
+        Some(value)
+    }
+
+    pub(crate) fn apply(self, regs: &mut [usize], a: usize, b: usize, c: usize) -> Option<()> {
+        let value = self.eval(regs, a, b)?;
+        *regs.get_mut(c)? = value;
+        Some(())
+    }
+}
+
+impl FromStr for Opcode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        NAMES
+            .iter()
+            .position(|&name| name == s)
+            .map(|i| ALL[i])
+            .ok_or(format!("unknown opcode {}", s))
+    }
+}
+
+// An instruction whose opcode is still just a number.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct Instruction {
+    opcode: usize,
+    a: usize,
+    b: usize,
+    c: usize,
+}
+lazy_static! {
+    // Example: 9 2 1 2
+    static ref INSTRUCTION: Regex = Regex::new(r"(?P<opcode>\d+) (?P<a>\d+) (?P<b>\d+) (?P<c>\d+)").unwrap();
+}
+impl FromStr for Instruction {
+    type Err = Box<dyn ::std::error::Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cap = INSTRUCTION
+            .captures(s)
+            .ok_or(format!("could not parse {}", s))?;
+        Ok(Instruction {
+            opcode: cap["opcode"].parse()?,
+            a: cap["a"].parse()?,
+            b: cap["b"].parse()?,
+            c: cap["c"].parse()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Sample {
+    before: Vec<usize>,
+    instruction: Instruction,
+    after: Vec<usize>,
+}
+lazy_static! {
+    // Example:
+    // Before: [3, 2, 1, 1]
+    // 9 2 1 2
+    // After:  [3, 2, 2, 1]
+    static ref SAMPLE: Regex = Regex::new(r"Before:\s*\[(?P<before>[\d, ]*)\]\s*(?P<instruction>[\d ]+?)\s*After:\s*\[(?P<after>[\d, ]*)\]").unwrap();
+}
+fn parse_registers(s: &str) -> Result<Vec<usize>, Box<dyn ::std::error::Error>> {
+    let regs = s
+        .split(',')
+        .map(|r| r.trim().parse())
+        .collect::<Result<_, _>>()?;
+    Ok(regs)
+}
+impl FromStr for Sample {
+    type Err = Box<dyn ::std::error::Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cap = SAMPLE.captures(s).ok_or(format!("could not parse {}", s))?;
+        Ok(Sample {
+            before: parse_registers(&cap["before"])?,
+            instruction: cap["instruction"].parse()?,
+            after: parse_registers(&cap["after"])?,
+        })
+    }
+}
+
+impl Sample {
+    fn matches(&self, op: Opcode) -> bool {
+        let mut regs = self.before.clone();
+        let Instruction { a, b, c, .. } = self.instruction;
+        op.apply(&mut regs, a, b, c).is_some() && regs == self.after
+    }
+
+    fn candidates(&self) -> HashSet<Opcode> {
+        ALL.iter().cloned().filter(|&op| self.matches(op)).collect()
+    }
+}
+
+// How many samples behave like three or more opcodes.
+fn count_ambiguous(samples: &[Sample]) -> usize {
+    samples.iter().filter(|s| s.candidates().len() >= 3).count()
+}
+
+// The puzzle input is a list of samples, then a few blank lines, then the test program.
+fn parse_input(s: &str) -> Result<(Vec<Sample>, Vec<Instruction>), Box<dyn ::std::error::Error>> {
+    let s = s.replace("\r\n", "\n");
+    let split = s.find("\n\n\n").unwrap_or(s.len());
+    let (samples, program) = s.split_at(split);
+    let samples = samples
+        .split("\n\n")
+        .filter(|chunk| !chunk.trim().is_empty())
+        .map(|chunk| chunk.parse())
+        .collect::<Result<_, _>>()?;
+    let program = program
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.parse())
+        .collect::<Result<_, _>>()?;
+    Ok((samples, program))
+}
+
+// Works out which opcode each number stands for. Every sample narrows down the candidates for
+// its number, and then any number with a single candidate left rules that opcode out for all
+// the others.
+fn deduce(samples: &[Sample]) -> Result<Vec<Opcode>, String> {
+    let mut candidates: Vec<HashSet<Opcode>> = vec![ALL.iter().cloned().collect(); ALL.len()];
+    for sample in samples {
+        let possible = candidates
+            .get_mut(sample.instruction.opcode)
+            .ok_or(format!(
+                "opcode {} is out of range",
+                sample.instruction.opcode
+            ))?;
+        *possible = possible
+            .intersection(&sample.candidates())
+            .cloned()
+            .collect();
+    }
+
+    let mut mapping: Vec<Option<Opcode>> = vec![None; ALL.len()];
+    while mapping.iter().any(|m| m.is_none()) {
+        let (number, op) = candidates
+            .iter()
+            .enumerate()
+            .filter(|&(number, possible)| mapping[number].is_none() && possible.len() == 1)
+            .map(|(number, possible)| (number, *possible.iter().next().unwrap()))
+            .next()
+            .ok_or_else(|| String::from("samples do not determine every opcode"))?;
+        mapping[number] = Some(op);
+        for possible in candidates.iter_mut() {
+            possible.remove(&op);
+        }
+    }
+    Ok(mapping.into_iter().map(|m| m.unwrap()).collect())
+}
+
+fn run(
+    program: &[Instruction],
+    mapping: &[Opcode],
+    registers: usize,
+) -> Result<Vec<usize>, String> {
+    let mut regs = vec![0; registers];
+    for inst in program {
+        let op = mapping
+            .get(inst.opcode)
+            .ok_or(format!("opcode {} is out of range", inst.opcode))?;
+        op.apply(&mut regs, inst.a, inst.b, inst.c)
+            .ok_or(format!("invalid register in {:?}", inst))?;
+    }
+    Ok(regs)
+}
+
+pub(crate) struct Day16;
+
+impl Puzzle for Day16 {
+    type Input = (Vec<Sample>, Vec<Instruction>);
+
+    fn parse(s: &str) -> Result<(Vec<Sample>, Vec<Instruction>), String> {
+        parse_input(s).map_err(|e| e.to_string())
+    }
+
+    fn part1((samples, _): &(Vec<Sample>, Vec<Instruction>)) -> Result<String, String> {
+        Ok(count_ambiguous(samples).to_string())
+    }
+
+    fn part2((samples, program): &(Vec<Sample>, Vec<Instruction>)) -> Result<String, String> {
+        let mapping = deduce(samples)?;
+        Ok(run(program, &mapping, 4)?[0].to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::Lcg;
+
+    #[test]
+    fn smoke() {
+        let sample: Sample = "Before: [3, 2, 1, 1]\n9 2 1 2\nAfter:  [3, 2, 2, 1]"
+            .parse()
+            .unwrap();
+        let expected = vec![Opcode::Addi, Opcode::Mulr, Opcode::Seti];
+        assert_eq!(sample.candidates(), expected.into_iter().collect());
+        assert_eq!("gtri".parse::<Opcode>(), Ok(Opcode::Gtri));
+        assert!(ALL.iter().all(|op| op.name().parse() == Ok(*op)));
+    }
+
+    // Samples for a shuffled opcode numbering, generated from a fixed seed.
+    fn synthetic(mapping: &[Opcode], count: usize) -> Vec<Sample> {
+        let mut rng = Lcg::new(16);
+        let mut rand = |n: usize| rng.below(n as u64) as usize;
+        (0..count)
+            .map(|_| {
+                let before: Vec<usize> = (0..4).map(|_| rand(4)).collect();
+                let instruction = Instruction {
+                    opcode: rand(16),
+                    a: rand(4),
+                    b: rand(4),
+                    c: rand(4),
+                };
+                let mut after = before.clone();
+                let Instruction { opcode, a, b, c } = instruction;
+                mapping[opcode].apply(&mut after, a, b, c).unwrap();
+                Sample {
+                    before,
+                    instruction,
+                    after,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parse() {
+        let input = "Before: [3, 2, 1, 1]
+9 2 1 2
+After:  [3, 2, 2, 1]
+
+Before: [0, 1, 2, 3]
+4 0 1 3
+After:  [0, 1, 2, 0]
+
+
+
+9 2 1 2
+4 0 1 3
+";
+        let (samples, program) = parse_input(input).unwrap();
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[1].after, vec![0, 1, 2, 0]);
+        assert_eq!(program.len(), 2);
+        assert_eq!(program[1], samples[1].instruction);
+    }
+
+    #[test]
+    fn ambiguous_samples() {
+        // The example sample behaves like three opcodes, which is just enough.
+        let (samples, _) =
+            parse_input("Before: [3, 2, 1, 1]\n9 2 1 2\nAfter:  [3, 2, 2, 1]").unwrap();
+        assert_eq!(count_ambiguous(&samples), 1);
+        let sample: Sample = "Before: [3, 2, 1, 1]\n9 2 1 2\nAfter:  [3, 2, 3, 1]"
+            .parse()
+            .unwrap();
+        assert_eq!(sample.candidates().len(), 2);
+        assert_eq!(count_ambiguous(&[sample]), 0);
+
+        let mut mapping = ALL.to_vec();
+        mapping.reverse();
+        let samples = synthetic(&mapping, 500);
+        assert_eq!(count_ambiguous(&samples), 424);
+        assert!(samples
+            .iter()
+            .all(|s| s.matches(mapping[s.instruction.opcode])));
+    }
+
+    #[test]
+    fn deduce_synthetic() {
+        let mut mapping = ALL.to_vec();
+        mapping.rotate_left(5);
+        let samples = synthetic(&mapping, 500);
+        assert_eq!(deduce(&samples), Ok(mapping.clone()));
+        assert!(deduce(&samples[..1]).is_err());
+
+        // r0 = 3 * 4 + 2, using the original numbering.
+        let numbered = |op: Opcode, a, b, c| Instruction {
+            opcode: mapping.iter().position(|&m| m == op).unwrap(),
+            a,
+            b,
+            c,
+        };
+        let program = vec![
+            numbered(Opcode::Seti, 3, 0, 1),
+            numbered(Opcode::Muli, 1, 4, 0),
+            numbered(Opcode::Addi, 0, 2, 0),
+        ];
+        assert_eq!(run(&program, &mapping, 4), Ok(vec![14, 3, 0, 0]));
+    }
+}
@@ -0,0 +1,302 @@
+use crate::day3::Rectangle;
+use crate::runner::Puzzle;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+const SAND: u8 = b'.';
+const CLAY: u8 = b'#';
+const FLOWING: u8 = b'|';
+const SETTLED: u8 = b'~';
+
+// Columns are signed, because water can spill past the leftmost vein even when it's at x=0.
+const SPRING: (i64, usize) = (500, 0);
+
+lazy_static! {
+    // Example: x=495, y=2..7
+    static ref PATTERN: Regex = Regex::new(r"^(?P<a>[xy])=(?P<a0>\d+), (?P<b>[xy])=(?P<b0>\d+)\.\.(?P<b1>\d+)$").unwrap();
+}
+// A vein is a one-tile-wide rectangle of clay.
+fn parse_vein(id: u32, s: &str) -> Result<Rectangle, String> {
+    let cap = PATTERN
+        .captures(s.trim())
+        .ok_or(format!("could not parse {}", s))?;
+    let num = |name: &str| -> Result<u32, String> {
+        cap[name]
+            .parse()
+            .map_err(|_| format!("could not parse {}", s))
+    };
+    let (a0, b0, b1) = (num("a0")?, num("b0")?, num("b1")?);
+    if b1 < b0 {
+        return Err(format!("empty range in {}", s));
+    }
+    match (&cap["a"], &cap["b"]) {
+        ("x", "y") => Ok(Rectangle {
+            id,
+            x: a0,
+            y: b0,
+            w: 1,
+            h: b1 - b0 + 1,
+        }),
+        ("y", "x") => Ok(Rectangle {
+            id,
+            x: b0,
+            y: a0,
+            w: b1 - b0 + 1,
+            h: 1,
+        }),
+        _ => Err(format!("expected one x and one y in {}", s)),
+    }
+}
+
+struct Scan {
+    // Columns `x0..x0 + width` and rows `0..height`, which leaves one column of sand on each
+    // side of the clay for water to fall down.
+    x0: i64,
+    width: usize,
+    height: usize,
+    tiles: Vec<u8>,
+    // The first row with any clay in it. Tiles above it don't count.
+    y_min: usize,
+}
+
+impl Scan {
+    fn new(veins: &[Rectangle]) -> Scan {
+        let x_min = veins
+            .iter()
+            .map(|v| i64::from(v.x))
+            .min()
+            .unwrap_or(SPRING.0);
+        let x_max = veins
+            .iter()
+            .map(|v| i64::from(v.x + v.w - 1))
+            .max()
+            .unwrap_or(SPRING.0);
+        let y_min = veins.iter().map(|v| v.y).min().unwrap_or(0) as usize;
+        let y_max = veins.iter().map(|v| v.y + v.h - 1).max().unwrap_or(0) as usize;
+        let x0 = x_min.min(SPRING.0) - 1;
+        let width = (x_max.max(SPRING.0) + 2 - x0) as usize;
+        let height = y_max + 1;
+        let mut scan = Scan {
+            x0,
+            width,
+            height,
+            tiles: vec![SAND; width * height],
+            y_min,
+        };
+        for vein in veins {
+            for p in vein.points() {
+                scan.set(i64::from(p.0), p.1 as usize, CLAY);
+            }
+        }
+        scan
+    }
+
+    fn get(&self, x: i64, y: usize) -> u8 {
+        self.tiles[self.width * y + (x - self.x0) as usize]
+    }
+
+    fn set(&mut self, x: i64, y: usize, tile: u8) {
+        self.tiles[self.width * y + (x - self.x0) as usize] = tile;
+    }
+
+    // Follows the water along row `y` from `x` in direction `dx`, and returns where it stops
+    // and whether that's because it hit a wall (rather than falling off an edge).
+    fn spread(&self, x: i64, y: usize, dx: i64) -> (i64, bool) {
+        let mut x = x;
+        loop {
+            let next = x + dx;
+            if self.get(next, y) == CLAY {
+                return (x, true);
+            }
+            x = next;
+            let below = self.get(x, y + 1);
+            if below == SAND || below == FLOWING {
+                return (x, false);
+            }
+        }
+    }
+
+    // Lets the water run from the spring until nothing changes. Rather than recursing, every
+    // point where water starts to fall (or needs to spread again) goes onto a stack.
+    fn fill(&mut self) {
+        let mut sources = vec![SPRING];
+        while let Some((x, mut y)) = sources.pop() {
+            while y + 1 < self.height && self.get(x, y + 1) == SAND {
+                y += 1;
+                self.set(x, y, FLOWING);
+            }
+            // The water either runs off the bottom of the scan, or joins a stream that's
+            // already been handled.
+            if y + 1 >= self.height || self.get(x, y + 1) == FLOWING {
+                continue;
+            }
+
+            let (left, left_wall) = self.spread(x, y, -1);
+            let (right, right_wall) = self.spread(x, y, 1);
+            if left_wall && right_wall {
+                for xx in left..=right {
+                    self.set(xx, y, SETTLED);
+                }
+                // Whatever was falling into this row now spreads out on top of it instead.
+                for xx in left..=right {
+                    if y > 0 && self.get(xx, y - 1) == FLOWING {
+                        sources.push((xx, y - 1));
+                    }
+                }
+            } else {
+                for xx in left..=right {
+                    self.set(xx, y, FLOWING);
+                }
+                if !left_wall {
+                    sources.push((left, y));
+                }
+                if !right_wall {
+                    sources.push((right, y));
+                }
+            }
+        }
+    }
+
+    fn count(&self, pred: fn(u8) -> bool) -> usize {
+        self.tiles[self.width * self.y_min..]
+            .iter()
+            .filter(|&&t| pred(t))
+            .count()
+    }
+
+    fn reachable(&self) -> usize {
+        self.count(|t| t == FLOWING || t == SETTLED)
+    }
+
+    fn retained(&self) -> usize {
+        self.count(|t| t == SETTLED)
+    }
+
+    fn pretty_print(&self) -> String {
+        let mut buf = String::new();
+        for y in 0..self.height {
+            for x in self.x0..self.x0 + self.width as i64 {
+                if (x, y) == SPRING {
+                    buf.push('+');
+                } else {
+                    buf.push(self.get(x, y) as char);
+                }
+            }
+            buf.push('\n');
+        }
+        buf
+    }
+}
+
+fn parse_veins(s: &str) -> Result<Vec<Rectangle>, String> {
+    s.lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(i, line)| parse_vein(i as u32, line))
+        .collect()
+}
+
+pub(crate) struct Day17;
+
+impl Puzzle for Day17 {
+    type Input = Vec<Rectangle>;
+
+    fn parse(s: &str) -> Result<Vec<Rectangle>, String> {
+        parse_veins(s)
+    }
+
+    fn part1(veins: &Vec<Rectangle>) -> Result<String, String> {
+        let mut scan = Scan::new(veins);
+        scan.fill();
+        Ok(scan.reachable().to_string())
+    }
+
+    fn part2(veins: &Vec<Rectangle>) -> Result<String, String> {
+        let mut scan = Scan::new(veins);
+        scan.fill();
+        Ok(scan.retained().to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(s: &str) -> Scan {
+        Scan::new(&parse_veins(s).unwrap())
+    }
+
+    #[test]
+    fn smoke() {
+        let mut scan = parse(
+            "x=495, y=2..7
+y=7, x=495..501
+x=501, y=3..7
+x=498, y=2..4
+x=506, y=1..2
+x=498, y=10..13
+x=504, y=10..13
+y=13, x=498..504",
+        );
+        scan.fill();
+        assert_eq!(
+            scan.pretty_print().trim(),
+            "
+......+.......
+......|.....#.
+.#..#||||...#.
+.#..#~~#|.....
+.#..#~~#|.....
+.#~~~~~#|.....
+.#~~~~~#|.....
+.#######|.....
+........|.....
+...|||||||||..
+...|#~~~~~#|..
+...|#~~~~~#|..
+...|#~~~~~#|..
+...|#######|.."
+                .trim()
+        );
+        assert_eq!(scan.reachable(), 57);
+        assert_eq!(scan.retained(), 29);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(parse_vein(0, "x=1, x=2..3").is_err());
+        assert!(parse_vein(0, "x=1, y=3..2").is_err());
+        assert!(parse_vein(0, "z=1, y=2..3").is_err());
+    }
+
+    #[test]
+    fn left_edge() {
+        // A shelf that starts in column 0, so water runs off its left end into column -1.
+        let mut scan = parse(
+            "x=200, y=1..1
+y=5, x=0..510
+x=0, y=6..8",
+        );
+        scan.fill();
+        assert_eq!(scan.x0, -1);
+        assert_eq!(scan.get(-1, 8), FLOWING);
+        assert_eq!(scan.get(511, 8), FLOWING);
+        assert_eq!(scan.retained(), 0);
+        // The drop from the spring, the whole shelf plus one on each end, and both falls.
+        assert_eq!(scan.reachable(), 3 + 513 + 2 * 4);
+    }
+
+    #[test]
+    fn tall() {
+        // A long drop into a basin, which overflows and falls off both sides.
+        let mut scan = parse(
+            "x=520, y=1..1
+y=300000, x=490..510
+x=490, y=299990..300000
+x=510, y=299990..300000",
+        );
+        scan.fill();
+        assert_eq!(scan.retained(), 19 * 10);
+        assert_eq!(scan.reachable(), 299_988 + 23 + 2 * 11 + 19 * 10);
+    }
+}
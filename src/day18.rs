@@ -0,0 +1,212 @@
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::runner::Puzzle;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+const OPEN: u8 = b'.';
+const TREES: u8 = b'|';
+const LUMBERYARD: u8 = b'#';
+
+// How many minutes `Area::resource_value_after` will simulate while looking for a cycle.
+const CYCLE_BUDGET: usize = 10_000;
+
+// After `start` minutes, the area repeats itself every `period` minutes.
+#[derive(Debug, Eq, PartialEq)]
+struct Cycle {
+    start: usize,
+    period: usize,
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub(crate) struct Area {
+    acres: Grid<u8>,
+}
+
+impl FromStr for Area {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let acres = Grid::parse(s, |b| b == OPEN || b == TREES || b == LUMBERYARD)?;
+        Ok(Area { acres })
+    }
+}
+
+impl fmt::Display for Area {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.acres)
+    }
+}
+
+// Where each kind of acre goes in a tally.
+fn kind(acre: u8) -> usize {
+    match acre {
+        OPEN => 0,
+        TREES => 1,
+        _ => 2,
+    }
+}
+
+impl Area {
+    // How many of the (up to eight) acres surrounding `p` are of each kind, indexed by `kind`.
+    fn neighbors(&self, p: Point) -> [usize; 3] {
+        let mut tally = [0; 3];
+        for n in self.acres.neighbors8(p) {
+            tally[kind(self.acres[n])] += 1;
+        }
+        tally
+    }
+
+    fn step(&mut self) {
+        let acres = &self.acres;
+        let next = Grid::from_fn(acres.width(), acres.height(), |p| {
+            let tally = self.neighbors(p);
+            let count = |acre| tally[kind(acre)];
+            match acres[p] {
+                OPEN if count(TREES) >= 3 => TREES,
+                TREES if count(LUMBERYARD) >= 3 => LUMBERYARD,
+                LUMBERYARD if count(LUMBERYARD) == 0 || count(TREES) == 0 => OPEN,
+                acre => acre,
+            }
+        });
+        self.acres = next;
+    }
+
+    fn resource_value(&self) -> usize {
+        let count = |kind| self.acres.values().filter(|&&a| a == kind).count();
+        count(TREES) * count(LUMBERYARD)
+    }
+
+    // Steps a copy of this area until it repeats a previous minute exactly, recording the
+    // resource value of every minute along the way.
+    fn find_cycle(&self, budget: usize) -> (Option<Cycle>, Vec<usize>) {
+        let mut area = self.clone();
+        let mut seen: HashMap<Grid<u8>, usize> = HashMap::new();
+        let mut history = Vec::new();
+        for minute in 0..=budget {
+            if let Some(&start) = seen.get(&area.acres) {
+                let cycle = Cycle {
+                    start,
+                    period: minute - start,
+                };
+                return (Some(cycle), history);
+            }
+            seen.insert(area.acres.clone(), minute);
+            history.push(area.resource_value());
+            area.step();
+        }
+        (None, history)
+    }
+
+    fn resource_value_after(&self, minutes: usize) -> Result<usize, String> {
+        let (cycle, history) = self.find_cycle(CYCLE_BUDGET);
+        if let Some(&value) = history.get(minutes) {
+            return Ok(value);
+        }
+        let Cycle { start, period } =
+            cycle.ok_or(format!("no cycle found within {} minutes", CYCLE_BUDGET))?;
+        Ok(history[start + (minutes - start) % period])
+    }
+}
+
+pub(crate) struct Day18;
+
+impl Puzzle for Day18 {
+    type Input = Area;
+
+    fn parse(s: &str) -> Result<Area, String> {
+        s.parse()
+    }
+
+    fn part1(area: &Area) -> Result<String, String> {
+        Ok(area.resource_value_after(10)?.to_string())
+    }
+
+    fn part2(area: &Area) -> Result<String, String> {
+        Ok(area.resource_value_after(1_000_000_000)?.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::Lcg;
+
+    const EXAMPLE: &str = ".#.#...|#.
+.....#|##|
+.|..|...#.
+..|#.....#
+#.#|||#|#|
+...#.||...
+.|....|...
+||...#|.#|
+|.||||..|.
+...#.|..|.";
+
+    // A pseudo-random 50x50 area, generated from a fixed seed.
+    fn synthetic() -> Area {
+        let mut rng = Lcg::new(18);
+        let acres = Grid::from_fn(50, 50, |_| [OPEN, TREES, LUMBERYARD][rng.below(3) as usize]);
+        Area { acres }
+    }
+
+    #[test]
+    fn smoke() {
+        let mut area: Area = EXAMPLE.parse().unwrap();
+        area.step();
+        assert_eq!(
+            area.to_string().trim(),
+            ".......##.
+......|###
+.|..|...#.
+..|#||...#
+..##||.|#|
+...#||||..
+||...|||..
+|||||.||.|
+||||||||||
+....||..|."
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!("..\n.".parse::<Area>().is_err());
+        assert!(".x".parse::<Area>().is_err());
+    }
+
+    #[test]
+    fn part1() {
+        let area: Area = EXAMPLE.parse().unwrap();
+        assert_eq!(area.resource_value_after(10), Ok(1147));
+
+        let mut area = area;
+        for _ in 0..10 {
+            area.step();
+        }
+        assert_eq!(area.resource_value(), 1147);
+    }
+
+    #[test]
+    fn cycle_extrapolation() {
+        let area = synthetic();
+        let (cycle, _) = area.find_cycle(CYCLE_BUDGET);
+        let Cycle { start, period } = cycle.unwrap();
+
+        // Extrapolating from the cycle agrees with simulating a few laps around it directly.
+        let minutes = start + 3 * period + period / 2;
+        let mut direct = area.clone();
+        for _ in 0..minutes {
+            direct.step();
+        }
+        assert_eq!(
+            area.resource_value_after(minutes),
+            Ok(direct.resource_value())
+        );
+        assert_eq!(
+            area.resource_value_after(1_000_000_000),
+            area.resource_value_after(start + (1_000_000_000 - start) % period)
+        );
+    }
+}
@@ -0,0 +1,402 @@
+use crate::day16::Opcode;
+use crate::runner::Puzzle;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::str::FromStr;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) struct Instruction {
+    pub(crate) op: Opcode,
+    pub(crate) a: usize,
+    pub(crate) b: usize,
+    pub(crate) c: usize,
+}
+lazy_static! {
+    // Example: seti 5 0 1
+    static ref INSTRUCTION: Regex = Regex::new(r"^(?P<op>[a-z]{4}) (?P<a>\d+) (?P<b>\d+) (?P<c>\d+)$").unwrap();
+    // Example: #ip 0
+    static ref IP: Regex = Regex::new(r"^#ip (?P<reg>\d+)$").unwrap();
+}
+impl FromStr for Instruction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let cap = INSTRUCTION
+            .captures(s.trim())
+            .ok_or(format!("could not parse {}", s))?;
+        let num = |name: &str| {
+            cap[name]
+                .parse()
+                .map_err(|_| format!("could not parse {}", s))
+        };
+        Ok(Instruction {
+            op: cap["op"].parse()?,
+            a: num("a")?,
+            b: num("b")?,
+            c: num("c")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Program {
+    // The register that the instruction pointer is bound to.
+    pub(crate) ip: usize,
+    pub(crate) instructions: Vec<Instruction>,
+}
+impl FromStr for Program {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut lines = s.lines().filter(|line| !line.trim().is_empty());
+        let first = lines.next().unwrap_or("");
+        let cap = IP
+            .captures(first.trim())
+            .ok_or(format!("could not parse {}", first))?;
+        let ip = cap["reg"]
+            .parse()
+            .map_err(|_| format!("could not parse {}", first))?;
+        let instructions = lines.map(|line| line.parse()).collect::<Result<_, _>>()?;
+        Ok(Program { ip, instructions })
+    }
+}
+
+// The hot loop in day 19 programs. With `a` and `b` both counting from 1 up to `n`, it adds `a`
+// to `acc` whenever `a * b == n`, so it's really just summing the divisors of `n`.
+#[derive(Debug, Eq, PartialEq)]
+struct DivisorSum {
+    // Where the loop starts (by setting `a` to 1), and the first instruction after it.
+    entry: usize,
+    exit: usize,
+    a: usize,
+    b: usize,
+    n: usize,
+    acc: usize,
+    // The scratch register used for products and comparisons.
+    tmp: usize,
+}
+
+impl Program {
+    // Looks for the divisor-summing double loop anywhere in the program. The operands of addr,
+    // mulr and eqrr can come in either order, so those are compared sorted.
+    fn find_divisor_sum(&self) -> Option<DivisorSum> {
+        use crate::day16::Opcode::*;
+        let shape = [
+            Seti, Seti, Mulr, Eqrr, Addr, Addi, Addr, Addi, Gtrr, Addr, Seti, Addi, Gtrr, Addr,
+            Seti,
+        ];
+        let ip = self.ip;
+        (0..self.instructions.len().saturating_sub(shape.len() - 1)).find_map(|entry| {
+            let w = &self.instructions[entry..entry + shape.len()];
+            if w.iter().zip(shape.iter()).any(|(inst, &op)| inst.op != op) {
+                return None;
+            }
+            let sorted = |x: usize, y: usize| (x.min(y), x.max(y));
+            let commutes = |i: usize, x: usize, y: usize, c: usize| {
+                (sorted(w[i].a, w[i].b), w[i].c) == (sorted(x, y), c)
+            };
+            let (a, b, tmp, acc, n) = (w[0].c, w[1].c, w[2].c, w[6].c, w[8].b);
+            let consistent = w[0].a == 1
+                && w[1].a == 1
+                && commutes(2, a, b, tmp)
+                && commutes(3, tmp, n, tmp)
+                && commutes(4, tmp, ip, ip)
+                && (w[5].a, w[5].b, w[5].c) == (ip, 1, ip)
+                && commutes(6, a, acc, acc)
+                && (w[7].a, w[7].b, w[7].c) == (b, 1, b)
+                && (w[8].a, w[8].c) == (b, tmp)
+                && commutes(9, ip, tmp, ip)
+                && (w[10].a, w[10].c) == (entry + 1, ip)
+                && (w[11].a, w[11].b, w[11].c) == (a, 1, a)
+                && (w[12].a, w[12].b, w[12].c) == (a, n, tmp)
+                && commutes(13, tmp, ip, ip)
+                && (w[14].a, w[14].c) == (entry, ip);
+            if consistent {
+                Some(DivisorSum {
+                    entry,
+                    exit: entry + shape.len(),
+                    a,
+                    b,
+                    n,
+                    acc,
+                    tmp,
+                })
+            } else {
+                None
+            }
+        })
+    }
+}
+
+fn sum_of_divisors(n: usize) -> usize {
+    let mut sum = 0;
+    let mut d = 1;
+    while d * d <= n {
+        if n.is_multiple_of(d) {
+            sum += d;
+            if d * d != n {
+                sum += n / d;
+            }
+        }
+        d += 1;
+    }
+    sum
+}
+
+pub(crate) struct Cpu<'a> {
+    program: &'a Program,
+    pub(crate) regs: Vec<usize>,
+    pub(crate) ip: usize,
+    // One line per executed instruction, in the puzzle's format, if tracing is turned on.
+    trace: Option<Vec<String>>,
+    shortcut: Option<DivisorSum>,
+}
+
+impl<'a> Cpu<'a> {
+    pub(crate) fn new(program: &'a Program, regs: Vec<usize>) -> Cpu<'a> {
+        Cpu {
+            program,
+            regs,
+            ip: 0,
+            trace: None,
+            shortcut: None,
+        }
+    }
+
+    fn with_trace(mut self) -> Cpu<'a> {
+        self.trace = Some(Vec::new());
+        self
+    }
+
+    // Skips straight past the divisor-summing loop, if the program has one.
+    fn accelerated(mut self) -> Cpu<'a> {
+        self.shortcut = self.program.find_divisor_sum();
+        self
+    }
+
+    pub(crate) fn current(&self) -> Option<&Instruction> {
+        self.program.instructions.get(self.ip)
+    }
+
+    // Executes a single instruction, and returns false once the program has halted.
+    pub(crate) fn step(&mut self) -> bool {
+        let inst = match self.current() {
+            Some(&inst) => inst,
+            None => return false,
+        };
+        if let Some(ref loop_) = self.shortcut {
+            if self.ip == loop_.entry {
+                let n = self.regs[loop_.n];
+                self.regs[loop_.acc] += sum_of_divisors(n);
+                self.regs[loop_.a] = n + 1;
+                self.regs[loop_.b] = n + 1;
+                self.regs[loop_.tmp] = 1;
+                self.ip = loop_.exit;
+                return true;
+            }
+        }
+
+        self.regs[self.program.ip] = self.ip;
+        let before = if self.trace.is_some() {
+            Some(self.regs.clone())
+        } else {
+            None
+        };
+        if inst
+            .op
+            .apply(&mut self.regs, inst.a, inst.b, inst.c)
+            .is_none()
+        {
+            return false;
+        }
+        if let (Some(trace), Some(before)) = (self.trace.as_mut(), before) {
+            trace.push(format!(
+                "ip={} {:?} {} {} {} {} {:?}",
+                self.ip,
+                before,
+                inst.op.name(),
+                inst.a,
+                inst.b,
+                inst.c,
+                self.regs
+            ));
+        }
+        self.ip = self.regs[self.program.ip] + 1;
+        true
+    }
+
+    fn run(&mut self) {
+        while self.step() {}
+    }
+}
+
+pub(crate) struct Day19;
+
+impl Day19 {
+    // What's left in register 0 once the program halts.
+    fn register0(program: &Program, start: usize) -> String {
+        let mut cpu = Cpu::new(program, vec![start, 0, 0, 0, 0, 0]).accelerated();
+        cpu.run();
+        cpu.regs[0].to_string()
+    }
+}
+
+impl Puzzle for Day19 {
+    type Input = Program;
+
+    fn parse(s: &str) -> Result<Program, String> {
+        s.parse()
+    }
+
+    fn part1(program: &Program) -> Result<String, String> {
+        Ok(Day19::register0(program, 0))
+    }
+
+    // Starting with register 0 at 1 makes the divisor sum far too slow to run (around 10^13
+    // instructions), so this only works if the loop can be skipped.
+    fn part2(program: &Program) -> Result<String, String> {
+        if program.find_divisor_sum().is_none() {
+            return Err(String::from("no divisor-summing loop to skip"));
+        }
+        Ok(Day19::register0(program, 1))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // The same shape of program as the real puzzle inputs, which sums the divisors of 836 (or
+    // 10551236 when register 0 starts at 1).
+    const DIVISORS: &str = "#ip 3
+addi 3 16 3
+seti 1 5 1
+seti 1 4 5
+mulr 1 5 4
+eqrr 4 2 4
+addr 4 3 3
+addi 3 1 3
+addr 1 0 0
+addi 5 1 5
+gtrr 5 2 4
+addr 3 4 3
+seti 2 7 3
+addi 1 1 1
+gtrr 1 2 4
+addr 4 3 3
+seti 1 3 3
+mulr 3 3 3
+seti 836 0 2
+addr 3 0 3
+seti 0 0 3
+seti 10551236 0 2
+seti 0 0 0
+seti 0 0 3";
+
+    #[test]
+    fn smoke() {
+        let program: Program = "#ip 0
+seti 5 0 1
+seti 6 0 2
+addi 0 1 0
+addr 1 2 3
+setr 1 0 0
+seti 8 0 4
+seti 9 0 5"
+            .parse()
+            .unwrap();
+        let mut cpu = Cpu::new(&program, vec![0; 6]).with_trace();
+        cpu.run();
+        assert_eq!(cpu.regs[0], 6);
+        assert_eq!(
+            cpu.trace.unwrap(),
+            vec![
+                "ip=0 [0, 0, 0, 0, 0, 0] seti 5 0 1 [0, 5, 0, 0, 0, 0]",
+                "ip=1 [1, 5, 0, 0, 0, 0] seti 6 0 2 [1, 5, 6, 0, 0, 0]",
+                "ip=2 [2, 5, 6, 0, 0, 0] addi 0 1 0 [3, 5, 6, 0, 0, 0]",
+                "ip=4 [4, 5, 6, 0, 0, 0] setr 1 0 0 [5, 5, 6, 0, 0, 0]",
+                "ip=6 [6, 5, 6, 0, 0, 0] seti 9 0 5 [6, 5, 6, 0, 0, 9]",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!("seti 5 0 1".parse::<Program>().is_err());
+        assert_eq!(
+            "#ip 0\nfoo 1 2 3".parse::<Program>().err(),
+            Some(String::from("could not parse foo 1 2 3"))
+        );
+        assert_eq!(
+            "#ip 0\nfrob 1 2 3".parse::<Program>().err(),
+            Some(String::from("unknown opcode frob"))
+        );
+    }
+
+    #[test]
+    fn part1() {
+        let program: Program = DIVISORS.parse().unwrap();
+        let mut plain = Cpu::new(&program, vec![0; 6]);
+        plain.run();
+        assert_eq!(plain.regs[0], 1680);
+
+        let mut fast = Cpu::new(&program, vec![0; 6]).accelerated();
+        fast.run();
+        assert_eq!(fast.regs, plain.regs);
+    }
+
+    #[test]
+    fn part2() {
+        let program: Program = DIVISORS.parse().unwrap();
+        assert_eq!(
+            program.find_divisor_sum(),
+            Some(DivisorSum {
+                entry: 1,
+                exit: 16,
+                a: 1,
+                b: 5,
+                n: 2,
+                acc: 0,
+                tmp: 4,
+            })
+        );
+        let mut cpu = Cpu::new(&program, vec![1, 0, 0, 0, 0, 0]).accelerated();
+        cpu.run();
+        assert_eq!(cpu.regs[0], 18514860);
+    }
+
+    #[test]
+    fn swapped_operands() {
+        let swapped = DIVISORS
+            .replace("mulr 1 5 4", "mulr 5 1 4")
+            .replace("eqrr 4 2 4", "eqrr 2 4 4")
+            .replace("addr 4 3 3", "addr 3 4 3")
+            .replace("addr 1 0 0", "addr 0 1 0");
+        let program: Program = swapped.parse().unwrap();
+        assert_eq!(
+            program.find_divisor_sum(),
+            DIVISORS.parse::<Program>().unwrap().find_divisor_sum()
+        );
+        assert_eq!(Day19::part2(&program), Ok(String::from("18514860")));
+
+        let mut plain = Cpu::new(&program, vec![0; 6]);
+        plain.run();
+        let mut fast = Cpu::new(&program, vec![0; 6]).accelerated();
+        fast.run();
+        assert_eq!(fast.regs, plain.regs);
+    }
+
+    #[test]
+    fn no_shortcut() {
+        // Swapping the operands of gtrr changes what the loop does, so it can't be skipped.
+        let program: Program = DIVISORS
+            .replace("gtrr 5 2 4", "gtrr 2 5 4")
+            .parse()
+            .unwrap();
+        assert_eq!(program.find_divisor_sum(), None);
+        assert_eq!(
+            Day19::part2(&program),
+            Err(String::from("no divisor-summing loop to skip"))
+        );
+    }
+}
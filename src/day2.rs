@@ -1,3 +1,4 @@
+use crate::runner::{parse_lines, Puzzle};
 use std::collections::HashMap;
 use std::str::Chars;
 
@@ -20,41 +21,46 @@ fn pair_that_differ_by(xs: &[String], count: usize) -> Option<(String, String)>
     None
 }
 
+pub(crate) struct Day2;
+
+impl Puzzle for Day2 {
+    type Input = Vec<String>;
+
+    fn parse(s: &str) -> Result<Vec<String>, String> {
+        parse_lines(s)
+    }
+
+    fn part1(ids: &Vec<String>) -> Result<String, String> {
+        let with = |n| {
+            ids.iter()
+                .filter(|s| counts(s.chars()).values().any(|&c| c == n))
+                .count()
+        };
+        Ok((with(2) * with(3)).to_string())
+    }
+
+    fn part2(ids: &Vec<String>) -> Result<String, String> {
+        let (a, b) = pair_that_differ_by(ids, 1)
+            .ok_or_else(|| String::from("no two ids differ by exactly one character"))?;
+        Ok(a.chars()
+            .zip(b.chars())
+            .filter_map(|(ai, bj)| if ai == bj { Some(ai) } else { None })
+            .collect())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use lazy_static::lazy_static;
-    use std::fs;
-
-    lazy_static! {
-        static ref INPUTS: Vec<String> = fs::read_to_string("data/day2/input")
-            .expect("read input file")
-            .lines()
-            .map(|s| String::from(s))
-            .collect();
-    }
+    use crate::runner;
 
     #[test]
     fn part1() {
-        let has2 = INPUTS
-            .iter()
-            .filter(|s| counts(s.chars()).values().any(|&c| c == 2))
-            .count();
-        let has3 = INPUTS
-            .iter()
-            .filter(|s| counts(s.chars()).values().any(|&c| c == 3))
-            .count();
-        assert_eq!(has2 * has3, 7134);
+        runner::check_answer::<Day2>(2, 1);
     }
 
     #[test]
     fn part2() {
-        let (a, b) = pair_that_differ_by(&INPUTS, 1).unwrap();
-        let common: String = a
-            .chars()
-            .zip(b.chars())
-            .filter_map(|(ai, bj)| if ai == bj { Some(ai) } else { None })
-            .collect();
-        assert_eq!(common, "kbqwtcvzhmhpoelrnaxydifyb");
+        runner::check_answer::<Day2>(2, 2);
     }
 }
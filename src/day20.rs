@@ -0,0 +1,236 @@
+use crate::geometry::Point;
+use crate::runner::Puzzle;
+use crate::search;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+// Rooms are connected by doors, and every room is reachable from the origin.
+pub(crate) struct Map {
+    doors: HashMap<Point, HashSet<Point>>,
+}
+
+impl Map {
+    fn add_door(&mut self, a: Point, b: Point) {
+        self.doors.entry(a).or_default().insert(b);
+        self.doors.entry(b).or_default().insert(a);
+    }
+
+    fn has_door(&self, a: Point, b: Point) -> bool {
+        self.doors.get(&a).is_some_and(|adj| adj.contains(&b))
+    }
+
+    // The fewest doors between the origin and every room.
+    fn distances(&self) -> HashMap<Point, usize> {
+        let neighbors = |cur: &Point| self.doors.get(cur).into_iter().flatten().cloned();
+        search::bfs(Some(Point(0, 0)), neighbors, |_| false, None).into_distances()
+    }
+
+    fn furthest(&self) -> usize {
+        self.distances().values().cloned().max().unwrap_or(0)
+    }
+
+    fn at_least(&self, doors: usize) -> usize {
+        self.distances().values().filter(|&&d| d >= doors).count()
+    }
+}
+
+// Rather than recursing into each group, keeps a stack of open groups. For each one it tracks
+// the rooms the group started from, and the rooms reached by the alternatives seen so far.
+impl FromStr for Map {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        if !s.starts_with('^') || !s.ends_with('$') || s.len() < 2 {
+            return Err(String::from("expected a route between ^ and $"));
+        }
+
+        let mut map = Map {
+            doors: HashMap::new(),
+        };
+        let mut cur: HashSet<Point> = vec![Point(0, 0)].into_iter().collect();
+        let mut groups: Vec<(HashSet<Point>, HashSet<Point>)> = Vec::new();
+        for (i, b) in s[1..s.len() - 1].bytes().enumerate() {
+            match b {
+                b'N' | b'E' | b'S' | b'W' => {
+                    let (dx, dy) = match b {
+                        b'N' => (0, -1),
+                        b'E' => (1, 0),
+                        b'S' => (0, 1),
+                        _ => (-1, 0),
+                    };
+                    cur = cur
+                        .into_iter()
+                        .map(|p| {
+                            let next = Point(p.0 + dx, p.1 + dy);
+                            map.add_door(p, next);
+                            next
+                        })
+                        .collect();
+                }
+                b'(' => groups.push((cur.clone(), HashSet::new())),
+                b'|' => {
+                    let (start, ends) = groups
+                        .last_mut()
+                        .ok_or(format!("unexpected | at {}", i + 1))?;
+                    ends.extend(cur.drain());
+                    cur = start.clone();
+                }
+                b')' => {
+                    let (_, ends) = groups.pop().ok_or(format!("unexpected ) at {}", i + 1))?;
+                    cur.extend(ends);
+                }
+                _ => return Err(format!("unexpected {:?} at {}", b as char, i + 1)),
+            }
+        }
+        if !groups.is_empty() {
+            return Err(String::from("unclosed group"));
+        }
+        Ok(map)
+    }
+}
+
+impl fmt::Display for Map {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let origin = Point(0, 0);
+        let rooms = || self.doors.keys().chain(Some(&origin));
+        let x0 = rooms().map(|p| p.0).min().unwrap();
+        let x1 = rooms().map(|p| p.0).max().unwrap();
+        let y0 = rooms().map(|p| p.1).min().unwrap();
+        let y1 = rooms().map(|p| p.1).max().unwrap();
+
+        writeln!(f, "{}", "#".repeat(2 * (x1 - x0 + 1) as usize + 1))?;
+        for y in y0..=y1 {
+            // The row of rooms, with any doors between them...
+            let mut rooms = String::from("#");
+            // ...and the row of walls below, with any doors through them.
+            let mut below = String::from("#");
+            for x in x0..=x1 {
+                let p = Point(x, y);
+                // Anything in the bounding box that the route never reaches is solid wall.
+                rooms.push(if p == origin {
+                    'X'
+                } else if self.doors.contains_key(&p) {
+                    '.'
+                } else {
+                    '#'
+                });
+                rooms.push(if self.has_door(p, Point(x + 1, y)) {
+                    '|'
+                } else {
+                    '#'
+                });
+                below.push(if self.has_door(p, Point(x, y + 1)) {
+                    '-'
+                } else {
+                    '#'
+                });
+                below.push('#');
+            }
+            writeln!(f, "{}", rooms)?;
+            writeln!(f, "{}", below)?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) struct Day20;
+
+impl Puzzle for Day20 {
+    type Input = Map;
+
+    fn parse(s: &str) -> Result<Map, String> {
+        s.parse()
+    }
+
+    fn part1(map: &Map) -> Result<String, String> {
+        Ok(map.furthest().to_string())
+    }
+
+    fn part2(map: &Map) -> Result<String, String> {
+        Ok(map.at_least(1000).to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn furthest(s: &str) -> usize {
+        s.parse::<Map>().unwrap().furthest()
+    }
+
+    #[test]
+    fn smoke() {
+        assert_eq!(furthest("^WNE$"), 3);
+        assert_eq!(furthest("^ENWWW(NEEE|SSE(EE|N))$"), 10);
+        assert_eq!(furthest("^ENNWSWW(NEWS|)SSSEEN(WNSE|)EE(SWEN|)NNN$"), 18);
+        assert_eq!(
+            furthest("^ESSWWN(E|NNENN(EESS(WNSE|)SSS|WWWSSSSE(SW|NNNE)))$"),
+            23
+        );
+        assert_eq!(
+            furthest("^WSSEESWWWNW(S|NENNEEEENN(ESSSSW(NWSW|SSEN)|WSWWN(E|WWS(E|SS))))$"),
+            31
+        );
+    }
+
+    #[test]
+    fn render() {
+        let map: Map = "^ENWWW(NEEE|SSE(EE|N))$".parse().unwrap();
+        assert_eq!(
+            map.to_string(),
+            "#########
+#.|.|.|.#
+#-#######
+#.|.|.|.#
+#-#####-#
+#.#.#X|.#
+#-#-#####
+#.|.|.|.#
+#########
+"
+        );
+    }
+
+    #[test]
+    fn render_unreached() {
+        let map: Map = "^N(E|W)$".parse().unwrap();
+        assert_eq!(
+            map.to_string(),
+            "#######
+#.|.|.#
+###-###
+###X###
+#######
+"
+        );
+    }
+
+    #[test]
+    fn at_least() {
+        let map: Map = "^ENWWW(NEEE|SSE(EE|N))$".parse().unwrap();
+        assert_eq!(map.at_least(10), 1);
+        assert_eq!(map.at_least(5), 11);
+        assert_eq!(map.at_least(0), 16);
+    }
+
+    #[test]
+    fn deeply_nested() {
+        let depth = 100_000;
+        let route = format!("^{}(E|W){}$", "(N".repeat(depth), ")".repeat(depth));
+        let map: Map = route.parse().unwrap();
+        assert_eq!(map.furthest(), depth + 1);
+        assert_eq!(map.at_least(depth), 3);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!("ENWWW".parse::<Map>().is_err());
+        assert!("^N(E|W$".parse::<Map>().is_err());
+        assert!("^NE)$".parse::<Map>().is_err());
+        assert!("^N|E$".parse::<Map>().is_err());
+        assert!("^NXE$".parse::<Map>().is_err());
+    }
+}
@@ -0,0 +1,275 @@
+use crate::day16::Opcode;
+use crate::day19::{Cpu, Program};
+use crate::runner::Puzzle;
+use std::collections::HashSet;
+
+// The only place the program reads register 0 is a single `eqrr` that halts the program when
+// it succeeds. Returns where that comparison is, and which register it compares against.
+fn find_comparison(program: &Program) -> Option<(usize, usize)> {
+    program
+        .instructions
+        .iter()
+        .enumerate()
+        .filter(|(_, inst)| inst.op == Opcode::Eqrr)
+        .filter_map(|(ip, inst)| match (inst.a, inst.b) {
+            (0, 0) => None,
+            (0, other) | (other, 0) => Some((ip, other)),
+            _ => None,
+        })
+        .next()
+}
+
+// The inner loop in day 21 programs, which divides by counting: it tries `q = 0, 1, ...` until
+// `(q + 1) * factor > n`, which leaves `n / factor` in `q`.
+#[derive(Debug, Eq, PartialEq)]
+struct Division {
+    // Where the loop starts (by setting `q` to 0), and the first instruction after it.
+    entry: usize,
+    exit: usize,
+    q: usize,
+    n: usize,
+    factor: usize,
+    // The scratch register used for products and comparisons.
+    tmp: usize,
+}
+
+// Looks for the dividing loop anywhere in the program.
+fn find_division(program: &Program) -> Option<Division> {
+    use crate::day16::Opcode::*;
+    let shape = [Seti, Addi, Muli, Gtrr, Addr, Addi, Seti, Addi, Seti];
+    let ip = program.ip;
+    let instructions = &program.instructions;
+    (0..instructions.len().saturating_sub(shape.len() - 1)).find_map(|entry| {
+        let w = &instructions[entry..entry + shape.len()];
+        if w.iter().zip(shape.iter()).any(|(inst, &op)| inst.op != op) {
+            return None;
+        }
+        let (q, tmp, factor, n) = (w[0].c, w[1].c, w[2].b, w[3].b);
+        let consistent = w[0].a == 0
+            && (w[1].a, w[1].b) == (q, 1)
+            && (w[2].a, w[2].c) == (tmp, tmp)
+            && (w[3].a, w[3].c) == (tmp, tmp)
+            && ((w[4].a, w[4].b) == (tmp, ip) || (w[4].a, w[4].b) == (ip, tmp))
+            && w[4].c == ip
+            && (w[5].a, w[5].b, w[5].c) == (ip, 1, ip)
+            && (w[6].a, w[6].c) == (entry + shape.len() - 1, ip)
+            && (w[7].a, w[7].b, w[7].c) == (q, 1, q)
+            && (w[8].a, w[8].c) == (entry, ip)
+            // A factor of 0 never stops, and the registers must all be different for the loop
+            // to work as described.
+            && factor > 0
+            && [q, tmp, n, ip].iter().collect::<HashSet<_>>().len() == 4;
+        if consistent {
+            Some(Division {
+                entry,
+                exit: entry + shape.len(),
+                q,
+                n,
+                factor,
+                tmp,
+            })
+        } else {
+            None
+        }
+    })
+}
+
+// The values the program compares register 0 against, in the order it compares them.
+struct Comparisons<'a> {
+    cpu: Cpu<'a>,
+    at: usize,
+    reg: usize,
+    // Stepping through the dividing loop takes up to hundreds of thousands of instructions per
+    // value, so it's skipped when the program has one.
+    division: Option<Division>,
+}
+
+impl<'a> Comparisons<'a> {
+    fn new(program: &'a Program) -> Option<Comparisons<'a>> {
+        let (at, reg) = find_comparison(program)?;
+        Some(Comparisons {
+            // With register 0 left at 0, the comparison never succeeds.
+            cpu: Cpu::new(program, vec![0; 6]),
+            at,
+            reg,
+            division: find_division(program),
+        })
+    }
+}
+
+impl<'a> Iterator for Comparisons<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        loop {
+            if let Some(ref division) = self.division {
+                if self.cpu.ip == division.entry {
+                    let regs = &mut self.cpu.regs;
+                    regs[division.q] = regs[division.n] / division.factor;
+                    regs[division.tmp] = 1;
+                    self.cpu.ip = division.exit;
+                    continue;
+                }
+            }
+            if self.cpu.ip == self.at {
+                let value = self.cpu.regs[self.reg];
+                self.cpu.step();
+                return Some(value);
+            }
+            if !self.cpu.step() {
+                return None;
+            }
+        }
+    }
+}
+
+// The last value before the first one that repeats. Once a value repeats the sequence cycles,
+// so that's the last new value the sequence will ever produce.
+fn last_before_repeat<I>(values: I) -> Option<usize>
+where
+    I: Iterator<Item = usize>,
+{
+    let mut seen = HashSet::new();
+    let mut last = None;
+    for value in values {
+        if seen.contains(&value) {
+            return last;
+        }
+        seen.insert(value);
+        last = Some(value);
+    }
+    last
+}
+
+pub(crate) struct Day21;
+
+impl Day21 {
+    fn comparisons(program: &Program) -> Result<Comparisons<'_>, String> {
+        Comparisons::new(program)
+            .ok_or_else(|| String::from("the program never compares against register 0"))
+    }
+}
+
+impl Puzzle for Day21 {
+    type Input = Program;
+
+    fn parse(s: &str) -> Result<Program, String> {
+        s.parse()
+    }
+
+    fn part1(program: &Program) -> Result<String, String> {
+        let first = Day21::comparisons(program)?
+            .next()
+            .ok_or_else(|| String::from("the program halts before comparing"))?;
+        Ok(first.to_string())
+    }
+
+    fn part2(program: &Program) -> Result<String, String> {
+        let last = last_before_repeat(Day21::comparisons(program)?)
+            .ok_or_else(|| String::from("the program halts before comparing"))?;
+        Ok(last.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // The same shape of program as the real puzzle inputs, but working with 12-bit values and
+    // dividing by 16 instead of 256, so the values start repeating much sooner.
+    const ACTIVATION: &str = "#ip 4
+seti 123 0 3
+bani 3 456 3
+eqri 3 72 3
+addr 3 4 4
+seti 0 0 4
+seti 0 0 3
+bori 3 256 2
+seti 1618 2 3
+bani 2 255 5
+addr 3 5 3
+bani 3 4095 3
+muli 3 65899 3
+bani 3 4095 3
+gtir 16 2 5
+addr 5 4 4
+addi 4 1 4
+seti 27 8 4
+seti 0 1 5
+addi 5 1 1
+muli 1 16 1
+gtrr 1 2 1
+addr 1 4 4
+addi 4 1 4
+seti 25 1 4
+addi 5 1 5
+seti 17 8 4
+setr 5 2 2
+seti 7 4 4
+eqrr 3 0 5
+addr 5 4 4
+seti 5 8 4";
+
+    // The real puzzle inputs' constants: 24-bit values, divided by 256.
+    fn full_width() -> Program {
+        ACTIVATION
+            .replace("bori 3 256 2", "bori 3 65536 2")
+            .replace("seti 1618 2 3", "seti 4921097 2 3")
+            .replace("bani 3 4095 3", "bani 3 16777215 3")
+            .replace("gtir 16 2 5", "gtir 256 2 5")
+            .replace("muli 1 16 1", "muli 1 256 1")
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn smoke() {
+        let program: Program = ACTIVATION.parse().unwrap();
+        assert_eq!(find_comparison(&program), Some((28, 3)));
+        assert_eq!(
+            find_division(&program),
+            Some(Division {
+                entry: 17,
+                exit: 26,
+                q: 5,
+                n: 2,
+                factor: 16,
+                tmp: 1,
+            })
+        );
+        assert_eq!(last_before_repeat(vec![1, 2, 3, 2, 4].into_iter()), Some(3));
+        assert_eq!(last_before_repeat(vec![5, 6].into_iter()), Some(6));
+    }
+
+    #[test]
+    fn part1() {
+        let program: Program = ACTIVATION.parse().unwrap();
+        let first = Comparisons::new(&program).unwrap().next().unwrap();
+        assert_eq!(first, 401);
+
+        // Starting with that value in register 0 halts the program at the first comparison.
+        let mut cpu = Cpu::new(&program, vec![first, 0, 0, 0, 0, 0]);
+        while cpu.step() {}
+        assert_eq!(cpu.regs[5], 1);
+    }
+
+    #[test]
+    fn part2() {
+        let program: Program = ACTIVATION.parse().unwrap();
+        let comparisons = Comparisons::new(&program).unwrap();
+        assert_eq!(last_before_repeat(comparisons), Some(3015));
+    }
+
+    #[test]
+    fn skipping_division() {
+        let program: Program = ACTIVATION.parse().unwrap();
+        let fast: Vec<usize> = Comparisons::new(&program).unwrap().take(200).collect();
+        let mut plain = Comparisons::new(&program).unwrap();
+        plain.division = None;
+        assert_eq!(plain.take(200).collect::<Vec<_>>(), fast);
+
+        let program = full_width();
+        assert_eq!(Day21::part1(&program), Ok(String::from("4797782")));
+        assert_eq!(Day21::part2(&program), Ok(String::from("6086461")));
+    }
+}
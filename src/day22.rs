@@ -0,0 +1,241 @@
+use crate::from_pattern;
+use crate::runner::Puzzle;
+use crate::search;
+
+const MODULUS: usize = 20183;
+const SWITCH_COST: usize = 7;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Region {
+    Rocky,
+    Wet,
+    Narrow,
+}
+
+impl Region {
+    fn risk(self) -> usize {
+        match self {
+            Region::Rocky => 0,
+            Region::Wet => 1,
+            Region::Narrow => 2,
+        }
+    }
+
+    fn allows(self, tool: Tool) -> bool {
+        match self {
+            Region::Rocky => tool != Tool::Neither,
+            Region::Wet => tool != Tool::Torch,
+            Region::Narrow => tool != Tool::ClimbingGear,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+enum Tool {
+    Neither,
+    Torch,
+    ClimbingGear,
+}
+
+const TOOLS: [Tool; 3] = [Tool::Neither, Tool::Torch, Tool::ClimbingGear];
+
+// The erosion level of every region computed so far, one row at a time. Each region depends on
+// the one above it and the one to its left, so the cave grows down and to the right on demand.
+struct Cave {
+    depth: usize,
+    target: (usize, usize),
+    width: usize,
+    erosion: Vec<Vec<usize>>,
+}
+
+impl Cave {
+    fn new(depth: usize, target: (usize, usize)) -> Cave {
+        let mut cave = Cave {
+            depth,
+            target,
+            width: 0,
+            erosion: Vec::new(),
+        };
+        cave.extend(target.0, target.1);
+        cave
+    }
+
+    fn geologic_index(&self, x: usize, y: usize) -> usize {
+        if (x, y) == (0, 0) || (x, y) == self.target {
+            0
+        } else if y == 0 {
+            x * 16807
+        } else if x == 0 {
+            y * 48271
+        } else {
+            self.erosion[y][x - 1] * self.erosion[y - 1][x]
+        }
+    }
+
+    // Makes sure the erosion level of (x, y) is known.
+    fn extend(&mut self, x: usize, y: usize) {
+        if x >= self.width {
+            // Grow a little extra, since a search that wanders this far will likely go further.
+            let width = (x + 1).max(2 * self.width);
+            for yy in 0..self.erosion.len() {
+                for xx in self.width..width {
+                    let level = (self.geologic_index(xx, yy) + self.depth) % MODULUS;
+                    self.erosion[yy].push(level);
+                }
+            }
+            self.width = width;
+        }
+        while y >= self.erosion.len() {
+            let yy = self.erosion.len();
+            self.erosion.push(Vec::with_capacity(self.width));
+            for xx in 0..self.width {
+                let level = (self.geologic_index(xx, yy) + self.depth) % MODULUS;
+                self.erosion[yy].push(level);
+            }
+        }
+    }
+
+    fn region(&mut self, x: usize, y: usize) -> Region {
+        self.extend(x, y);
+        match self.erosion[y][x] % 3 {
+            0 => Region::Rocky,
+            1 => Region::Wet,
+            _ => Region::Narrow,
+        }
+    }
+
+    // The total risk of the rectangle from the mouth of the cave to the target.
+    fn risk(&mut self) -> usize {
+        let (tx, ty) = self.target;
+        let mut total = 0;
+        for y in 0..=ty {
+            for x in 0..=tx {
+                total += self.region(x, y).risk();
+            }
+        }
+        total
+    }
+
+    // The fewest minutes needed to reach the target holding the torch, starting from the mouth
+    // of the cave holding the torch.
+    fn fastest_route(&mut self) -> usize {
+        let (tx, ty) = self.target;
+        let start = (0, 0, Tool::Torch);
+        let goal = (tx, ty, Tool::Torch);
+        let moves = |&(x, y, tool): &(usize, usize, Tool)| {
+            let here = self.region(x, y);
+            // Every region allows exactly two tools, so there's only ever one to switch to.
+            let other = TOOLS
+                .iter()
+                .cloned()
+                .find(|&other| other != tool && here.allows(other));
+            let step = |cave: &mut Cave, x: usize, y: usize| {
+                if cave.region(x, y).allows(tool) {
+                    Some(((x, y, tool), 1))
+                } else {
+                    None
+                }
+            };
+            let moves = [
+                other.map(|other| ((x, y, other), SWITCH_COST)),
+                step(self, x + 1, y),
+                step(self, x, y + 1),
+                x.checked_sub(1).and_then(|left| step(self, left, y)),
+                y.checked_sub(1).and_then(|up| step(self, x, up)),
+            ];
+            IntoIterator::into_iter(moves).flatten()
+        };
+        // Each step gets at most one square closer, and arriving with any other tool means
+        // switching to the torch at the end.
+        let heuristic = |&(x, y, tool): &(usize, usize, Tool)| {
+            let switch = if tool == Tool::Torch { 0 } else { SWITCH_COST };
+            x.abs_diff(tx) + y.abs_diff(ty) + switch
+        };
+        search::astar(Some(start), moves, heuristic, |&n| n == goal, None)
+            .distance(&goal)
+            .expect("the target is always reachable")
+    }
+}
+
+from_pattern! {
+    // Example: depth: 4848
+    //          target: 15,700
+    #[pattern = r"^depth: (?P<depth>\d+)\s+target: (?P<target>\d+,\d+)"]
+    pub(crate) struct Scan {
+        depth: usize,
+        target: (usize, usize) = parse_target,
+    }
+}
+
+fn parse_target(s: &str) -> Result<(usize, usize), String> {
+    let mut coords = s.split(',').map(|c| c.parse::<usize>());
+    match (coords.next(), coords.next()) {
+        (Some(Ok(x)), Some(Ok(y))) => Ok((x, y)),
+        _ => Err(format!("could not parse {}", s)),
+    }
+}
+
+pub(crate) struct Day22;
+
+impl Puzzle for Day22 {
+    type Input = Scan;
+
+    fn parse(s: &str) -> Result<Scan, String> {
+        s.trim().parse().map_err(|e| format!("{}", e))
+    }
+
+    fn part1(scan: &Scan) -> Result<String, String> {
+        Ok(Cave::new(scan.depth, scan.target).risk().to_string())
+    }
+
+    fn part2(scan: &Scan) -> Result<String, String> {
+        Ok(Cave::new(scan.depth, scan.target)
+            .fastest_route()
+            .to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::runner;
+
+    #[test]
+    fn smoke() {
+        let mut cave = Cave::new(510, (10, 10));
+        assert_eq!(cave.erosion[0][0], 510);
+        assert_eq!(cave.erosion[1][1], 1805);
+        assert_eq!(cave.region(0, 0), Region::Rocky);
+        assert_eq!(cave.region(1, 0), Region::Wet);
+        assert_eq!(cave.region(0, 1), Region::Rocky);
+        assert_eq!(cave.region(1, 1), Region::Narrow);
+        assert_eq!(cave.region(10, 10), Region::Rocky);
+        assert_eq!(cave.risk(), 114);
+        assert_eq!(cave.fastest_route(), 45);
+    }
+
+    #[test]
+    fn lazy_extension() {
+        // Growing the cave piece by piece gives the same levels as computing it all at once.
+        let mut lazy = Cave::new(510, (10, 10));
+        lazy.extend(30, 12);
+        lazy.extend(12, 40);
+        let mut eager = Cave::new(510, (10, 10));
+        eager.extend(60, 40);
+        for y in 0..=40 {
+            for x in 0..=30 {
+                assert_eq!(lazy.erosion[y][x], eager.erosion[y][x]);
+            }
+        }
+    }
+
+    #[test]
+    fn part1() {
+        runner::check_answer::<Day22>(22, 1);
+    }
+
+    #[test]
+    fn part2() {
+        runner::check_answer::<Day22>(22, 2);
+    }
+}
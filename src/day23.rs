@@ -0,0 +1,254 @@
+use crate::geometry::{BoundingBox, Coordinates, Point3};
+use crate::runner::{parse_lines, Puzzle};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::str::FromStr;
+
+const ORIGIN: Point3 = Point3(0, 0, 0);
+
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) struct Nanobot {
+    pos: Point3,
+    r: usize,
+}
+
+lazy_static! {
+    // Example: pos=<0,-2,13>, r=4
+    static ref PATTERN: Regex = Regex::new(r"^pos=<(?P<x>-?\d+),(?P<y>-?\d+),(?P<z>-?\d+)>, r=(?P<r>\d+)$").unwrap();
+}
+impl FromStr for Nanobot {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Nanobot, String> {
+        let cap = PATTERN
+            .captures(s.trim())
+            .ok_or(format!("could not parse {}", s))?;
+        let num = |name: &str| -> Result<i64, String> {
+            cap[name]
+                .parse()
+                .map_err(|_| format!("could not parse {}", s))
+        };
+        Ok(Nanobot {
+            pos: Point3(num("x")?, num("y")?, num("z")?),
+            r: num("r")? as usize,
+        })
+    }
+}
+
+impl Nanobot {
+    fn in_range(&self, p: &Point3) -> bool {
+        self.pos.manhattan(p) <= self.r
+    }
+}
+
+fn in_range_of_strongest(bots: &[Nanobot]) -> usize {
+    match bots.iter().max_by_key(|bot| bot.r) {
+        Some(strongest) => bots
+            .iter()
+            .filter(|bot| strongest.in_range(&bot.pos))
+            .count(),
+        None => 0,
+    }
+}
+
+// An axis-aligned cube of `size` x `size` x `size` points, with `lo` as its lowest corner.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Debug)]
+struct Cube {
+    lo: Point3,
+    size: i64,
+}
+
+impl Cube {
+    fn bounds(&self) -> BoundingBox<Point3> {
+        BoundingBox {
+            min: self.lo,
+            max: self.lo + Point3(1, 1, 1) * (self.size - 1),
+        }
+    }
+
+    fn octants(&self) -> Vec<Cube> {
+        let half = self.size / 2;
+        let mut octants = Vec::with_capacity(8);
+        for &dx in &[0, half] {
+            for &dy in &[0, half] {
+                for &dz in &[0, half] {
+                    octants.push(Cube {
+                        lo: Point3(self.lo.0 + dx, self.lo.1 + dy, self.lo.2 + dz),
+                        size: half,
+                    });
+                }
+            }
+        }
+        octants
+    }
+}
+
+// The point in range of the most nanobots, breaking ties by distance to the origin, and how
+// many nanobots it's in range of.
+//
+// Starting from a cube around every nanobot's range, repeatedly splits whichever cube is most
+// promising into octants. A cube's count of nanobots that reach any part of it is an upper bound
+// for every point inside, and its distance to the origin is a lower bound, so the first single
+// point to come off the queue is the best one.
+fn best_point(bots: &[Nanobot]) -> (Point3, usize) {
+    let extent = bots
+        .iter()
+        .map(|bot| {
+            let Point3(x, y, z) = bot.pos;
+            x.abs().max(y.abs()).max(z.abs()) + bot.r as i64
+        })
+        .max()
+        .unwrap_or(0);
+    let mut size = 1;
+    while size < 2 * extent + 1 {
+        size *= 2;
+    }
+
+    let count = |cube: &Cube| {
+        let bounds = cube.bounds();
+        bots.iter()
+            .filter(|bot| bounds.distance_to(&bot.pos) <= bot.r)
+            .count()
+    };
+    let mut q = BinaryHeap::new();
+    let root = Cube {
+        lo: Point3(-extent, -extent, -extent),
+        size,
+    };
+    q.push((
+        count(&root),
+        Reverse(root.bounds().distance_to(&ORIGIN)),
+        Reverse(root),
+    ));
+    while let Some((n, _, Reverse(cube))) = q.pop() {
+        if cube.size == 1 {
+            return (cube.lo, n);
+        }
+        for octant in cube.octants() {
+            q.push((
+                count(&octant),
+                Reverse(octant.bounds().distance_to(&ORIGIN)),
+                Reverse(octant),
+            ));
+        }
+    }
+    unreachable!("the queue only empties after reaching a single point")
+}
+
+pub(crate) struct Day23;
+
+impl Puzzle for Day23 {
+    type Input = Vec<Nanobot>;
+
+    fn parse(s: &str) -> Result<Vec<Nanobot>, String> {
+        parse_lines(s)
+    }
+
+    fn part1(bots: &Vec<Nanobot>) -> Result<String, String> {
+        Ok(in_range_of_strongest(bots).to_string())
+    }
+
+    fn part2(bots: &Vec<Nanobot>) -> Result<String, String> {
+        let (p, _) = best_point(bots);
+        Ok(p.manhattan(&ORIGIN).to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::Lcg;
+
+    fn parse(s: &str) -> Vec<Nanobot> {
+        s.lines().map(|line| line.parse().unwrap()).collect()
+    }
+
+    const EXAMPLE: &str = "pos=<10,12,12>, r=2
+pos=<12,14,12>, r=2
+pos=<16,12,12>, r=4
+pos=<14,14,14>, r=6
+pos=<50,50,50>, r=200
+pos=<10,10,10>, r=5";
+
+    #[test]
+    fn smoke() {
+        assert_eq!(
+            "pos=<0,-2,13>, r=4".parse::<Nanobot>(),
+            Ok(Nanobot {
+                pos: Point3(0, -2, 13),
+                r: 4
+            })
+        );
+        assert!("pos=<0,0>, r=4".parse::<Nanobot>().is_err());
+        assert_eq!(Point3(1, -2, 3).manhattan(&Point3(-1, 2, 0)), 9);
+
+        let bots = parse(
+            "pos=<0,0,0>, r=4
+pos=<1,0,0>, r=1
+pos=<4,0,0>, r=3
+pos=<0,2,0>, r=1
+pos=<0,5,0>, r=3
+pos=<0,0,3>, r=1
+pos=<1,1,1>, r=1
+pos=<1,1,2>, r=1
+pos=<1,3,1>, r=1",
+        );
+        assert_eq!(in_range_of_strongest(&bots), 7);
+    }
+
+    #[test]
+    fn best_point_example() {
+        let bots = parse(EXAMPLE);
+        let (p, n) = best_point(&bots);
+        assert_eq!((p, n), (Point3(12, 12, 12), 5));
+        assert_eq!(p.manhattan(&ORIGIN), 36);
+    }
+
+    #[test]
+    fn large_coordinates() {
+        let scale = 10_000_000;
+        let bots: Vec<Nanobot> = parse(EXAMPLE)
+            .into_iter()
+            .map(|bot| Nanobot {
+                pos: Point3(bot.pos.0 * scale, bot.pos.1 * scale, bot.pos.2 * scale),
+                r: bot.r * scale as usize,
+            })
+            .collect();
+        let (p, n) = best_point(&bots);
+        assert_eq!(n, 5);
+        assert_eq!(p.manhattan(&ORIGIN), 36 * scale as usize);
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let mut rng = Lcg::new(23);
+        let mut next = |n| rng.below(n);
+        for _ in 0..200 {
+            let bots: Vec<Nanobot> = (0..1 + next(8))
+                .map(|_| Nanobot {
+                    pos: Point3(
+                        next(17) as i64 - 8,
+                        next(17) as i64 - 8,
+                        next(17) as i64 - 8,
+                    ),
+                    r: next(7) as usize,
+                })
+                .collect();
+
+            let mut expected = (0, Reverse(0));
+            for x in -15..=15 {
+                for y in -15..=15 {
+                    for z in -15..=15 {
+                        let p = Point3(x, y, z);
+                        let n = bots.iter().filter(|bot| bot.in_range(&p)).count();
+                        expected = expected.max((n, Reverse(p.manhattan(&ORIGIN))));
+                    }
+                }
+            }
+            let (p, n) = best_point(&bots);
+            assert_eq!((n, Reverse(p.manhattan(&ORIGIN))), expected);
+        }
+    }
+}
@@ -0,0 +1,442 @@
+use crate::runner::Puzzle;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+enum Army {
+    ImmuneSystem,
+    Infection,
+}
+
+impl fmt::Display for Army {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Army::ImmuneSystem => write!(f, "Immune System"),
+            Army::Infection => write!(f, "Infection"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Group {
+    army: Army,
+    // Groups are numbered from 1 within their army, in the order they're listed.
+    id: usize,
+    units: usize,
+    hp: usize,
+    weak: Vec<String>,
+    immune: Vec<String>,
+    damage: usize,
+    kind: String,
+    initiative: usize,
+}
+
+lazy_static! {
+    // Example: 17 units each with 5390 hit points (weak to radiation, bludgeoning) with an attack that does 4507 fire damage at initiative 2
+    static ref PATTERN: Regex = Regex::new(r"^(?P<units>\d+) units each with (?P<hp>\d+) hit points (?:\((?P<modifiers>[^)]*)\) )?with an attack that does (?P<damage>\d+) (?P<kind>\w+) damage at initiative (?P<initiative>\d+)$").unwrap();
+    // Example: immune to fire
+    static ref MODIFIER: Regex = Regex::new(r"^(?P<which>weak|immune) to (?P<kinds>\w+(?:, \w+)*)$").unwrap();
+}
+
+fn parse_group(army: Army, id: usize, s: &str) -> Result<Group, String> {
+    let cap = PATTERN
+        .captures(s.trim())
+        .ok_or(format!("could not parse {}", s))?;
+    let num = |name: &str| -> Result<usize, String> {
+        cap[name]
+            .parse()
+            .map_err(|_| format!("could not parse {}", s))
+    };
+    let mut group = Group {
+        army,
+        id,
+        units: num("units")?,
+        hp: num("hp")?,
+        weak: Vec::new(),
+        immune: Vec::new(),
+        damage: num("damage")?,
+        kind: cap["kind"].to_owned(),
+        initiative: num("initiative")?,
+    };
+    if let Some(modifiers) = cap.name("modifiers") {
+        for modifier in modifiers.as_str().split("; ") {
+            let m = MODIFIER
+                .captures(modifier)
+                .ok_or(format!("could not parse {}", modifier))?;
+            let kinds = m["kinds"].split(", ").map(String::from);
+            match &m["which"] {
+                "weak" => group.weak.extend(kinds),
+                _ => group.immune.extend(kinds),
+            }
+        }
+    }
+    Ok(group)
+}
+
+impl Group {
+    fn effective_power(&self) -> usize {
+        self.units * self.damage
+    }
+
+    // How much damage this group would deal to `other`, ignoring how many units it has.
+    fn damage_to(&self, other: &Group) -> usize {
+        if other.immune.contains(&self.kind) {
+            0
+        } else if other.weak.contains(&self.kind) {
+            2 * self.effective_power()
+        } else {
+            self.effective_power()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Attack {
+    army: Army,
+    attacker: usize,
+    defender: usize,
+    killed: usize,
+}
+
+impl fmt::Display for Attack {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} group {} attacks defending group {}, killing {} units",
+            self.army, self.attacker, self.defender, self.killed
+        )
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum Outcome {
+    Victory(Army, usize),
+    // Neither army can kill any more units, so the fight would go on forever.
+    Stalemate,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Battle {
+    groups: Vec<Group>,
+}
+
+impl FromStr for Battle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Battle, String> {
+        let mut groups = Vec::new();
+        let mut army = None;
+        let mut id = 0;
+        for line in s.lines().map(|line| line.trim()) {
+            match line {
+                "" => {}
+                "Immune System:" | "Infection:" => {
+                    army = Some(if line == "Infection:" {
+                        Army::Infection
+                    } else {
+                        Army::ImmuneSystem
+                    });
+                    id = 0;
+                }
+                _ => {
+                    let army = army.ok_or(format!("no army given for {}", line))?;
+                    id += 1;
+                    groups.push(parse_group(army, id, line)?);
+                }
+            }
+        }
+        Ok(Battle { groups })
+    }
+}
+
+impl fmt::Display for Battle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for &army in &[Army::ImmuneSystem, Army::Infection] {
+            writeln!(f, "{}:", army)?;
+            let alive: Vec<&Group> = self.groups.iter().filter(|g| g.army == army).collect();
+            if alive.is_empty() {
+                writeln!(f, "No groups remain.")?;
+            }
+            for g in alive {
+                writeln!(f, "Group {} contains {} units", g.id, g.units)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Battle {
+    fn boost(&mut self, boost: usize) {
+        for g in self.groups.iter_mut() {
+            if g.army == Army::ImmuneSystem {
+                g.damage += boost;
+            }
+        }
+    }
+
+    // Each group picks which enemy group it will attack, if any. Returns the pairs of indices.
+    fn select_targets(&self) -> Vec<(usize, usize)> {
+        let mut order: Vec<usize> = (0..self.groups.len()).collect();
+        order.sort_by_key(|&i| {
+            let g = &self.groups[i];
+            (Reverse(g.effective_power()), Reverse(g.initiative))
+        });
+
+        let mut taken = HashSet::new();
+        let mut targets = Vec::new();
+        for i in order {
+            let attacker = &self.groups[i];
+            let target = (0..self.groups.len())
+                .filter(|&j| self.groups[j].army != attacker.army && !taken.contains(&j))
+                .filter(|&j| attacker.damage_to(&self.groups[j]) > 0)
+                .max_by_key(|&j| {
+                    let defender = &self.groups[j];
+                    (
+                        attacker.damage_to(defender),
+                        defender.effective_power(),
+                        defender.initiative,
+                    )
+                });
+            if let Some(j) = target {
+                taken.insert(j);
+                targets.push((i, j));
+            }
+        }
+        targets
+    }
+
+    // Fights a single round, and returns every attack that happened, in order.
+    fn round(&mut self) -> Vec<Attack> {
+        let mut targets = self.select_targets();
+        targets.sort_by_key(|&(i, _)| Reverse(self.groups[i].initiative));
+
+        let mut attacks = Vec::new();
+        for (i, j) in targets {
+            if self.groups[i].units == 0 {
+                continue;
+            }
+            let damage = self.groups[i].damage_to(&self.groups[j]);
+            let defender = &mut self.groups[j];
+            let killed = (damage / defender.hp).min(defender.units);
+            defender.units -= killed;
+            attacks.push(Attack {
+                army: self.groups[i].army,
+                attacker: self.groups[i].id,
+                defender: self.groups[j].id,
+                killed,
+            });
+        }
+        self.groups.retain(|g| g.units > 0);
+        attacks
+    }
+
+    fn fight(&mut self) -> Outcome {
+        loop {
+            let armies: HashSet<Army> = self.groups.iter().map(|g| g.army).collect();
+            if armies.len() < 2 {
+                return match armies.into_iter().next() {
+                    Some(army) => Outcome::Victory(army, self.units()),
+                    None => Outcome::Stalemate,
+                };
+            }
+            if self.round().iter().all(|attack| attack.killed == 0) {
+                return Outcome::Stalemate;
+            }
+        }
+    }
+
+    fn units(&self) -> usize {
+        self.groups.iter().map(|g| g.units).sum()
+    }
+
+    // The smallest boost that lets the immune system win, and how many units it has left.
+    fn smallest_winning_boost(&self) -> Option<(usize, usize)> {
+        // With this much extra damage, any immune group that can hurt an infection group wipes
+        // it out in a single attack.
+        let limit = self
+            .groups
+            .iter()
+            .filter(|g| g.army == Army::Infection)
+            .map(|g| g.units * g.hp)
+            .max()
+            .unwrap_or(0);
+        let win = |boost| {
+            let mut battle = self.clone();
+            battle.boost(boost);
+            match battle.fight() {
+                Outcome::Victory(Army::ImmuneSystem, units) => Some((boost, units)),
+                _ => None,
+            }
+        };
+
+        // No amount of damage helps against an infection group that's immune to every attack.
+        let hopeless = self
+            .groups
+            .iter()
+            .filter(|g| g.army == Army::Infection)
+            .any(|g| {
+                self.groups
+                    .iter()
+                    .filter(|i| i.army == Army::ImmuneSystem)
+                    .all(|i| g.immune.contains(&i.kind))
+            });
+        if hopeless {
+            return None;
+        }
+
+        // A bigger boost doesn't always do better (stalemates come and go), so try every boost
+        // in turn.
+        (0..=limit).find_map(win)
+    }
+}
+
+pub(crate) struct Day24;
+
+impl Puzzle for Day24 {
+    type Input = Battle;
+
+    fn parse(s: &str) -> Result<Battle, String> {
+        s.parse()
+    }
+
+    fn part1(battle: &Battle) -> Result<String, String> {
+        match battle.clone().fight() {
+            Outcome::Victory(_, units) => Ok(units.to_string()),
+            Outcome::Stalemate => Err(String::from("the battle ends in a stalemate")),
+        }
+    }
+
+    fn part2(battle: &Battle) -> Result<String, String> {
+        let (_, units) = battle
+            .smallest_winning_boost()
+            .ok_or_else(|| String::from("no boost lets the immune system win"))?;
+        Ok(units.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "Immune System:
+17 units each with 5390 hit points (weak to radiation, bludgeoning) with an attack that does 4507 fire damage at initiative 2
+989 units each with 1274 hit points (immune to fire; weak to bludgeoning, slashing) with an attack that does 25 slashing damage at initiative 3
+
+Infection:
+801 units each with 4706 hit points (weak to radiation) with an attack that does 116 bludgeoning damage at initiative 1
+4485 units each with 2961 hit points (immune to radiation; weak to fire, cold) with an attack that does 12 slashing damage at initiative 4";
+
+    #[test]
+    fn smoke() {
+        let battle: Battle = EXAMPLE.parse().unwrap();
+        assert_eq!(
+            battle.groups[1],
+            Group {
+                army: Army::ImmuneSystem,
+                id: 2,
+                units: 989,
+                hp: 1274,
+                weak: vec![String::from("bludgeoning"), String::from("slashing")],
+                immune: vec![String::from("fire")],
+                damage: 25,
+                kind: String::from("slashing"),
+                initiative: 3,
+            }
+        );
+        assert!(parse_group(
+            Army::Infection,
+            1,
+            "1 units each with 2 hit points with an attack that does 3 cold damage at initiative 4"
+        )
+        .is_ok());
+        assert!(parse_group(
+            Army::Infection,
+            1,
+            "1 units each with 2 hit points (strong to fire) with an attack that does 3 cold damage at initiative 4"
+        )
+        .is_err());
+        assert!("17 units each with 5390 hit points"
+            .parse::<Battle>()
+            .is_err());
+    }
+
+    #[test]
+    fn first_round() {
+        let mut battle: Battle = EXAMPLE.parse().unwrap();
+        let attacks: Vec<String> = battle.round().iter().map(|a| a.to_string()).collect();
+        assert_eq!(
+            attacks,
+            vec![
+                "Infection group 2 attacks defending group 2, killing 84 units",
+                "Immune System group 2 attacks defending group 1, killing 4 units",
+                "Immune System group 1 attacks defending group 2, killing 51 units",
+                "Infection group 1 attacks defending group 1, killing 17 units",
+            ]
+        );
+        assert_eq!(
+            battle.to_string(),
+            "Immune System:
+Group 2 contains 905 units
+Infection:
+Group 1 contains 797 units
+Group 2 contains 4434 units
+"
+        );
+    }
+
+    #[test]
+    fn part1() {
+        let mut battle: Battle = EXAMPLE.parse().unwrap();
+        assert_eq!(battle.fight(), Outcome::Victory(Army::Infection, 5216));
+    }
+
+    #[test]
+    fn part2() {
+        let battle: Battle = EXAMPLE.parse().unwrap();
+        let mut boosted = battle.clone();
+        boosted.boost(1570);
+        assert_eq!(boosted.fight(), Outcome::Victory(Army::ImmuneSystem, 51));
+        assert_eq!(battle.smallest_winning_boost(), Some((1570, 51)));
+    }
+
+    #[test]
+    fn stalemate() {
+        // Neither group can do enough damage to kill a single unit of the other.
+        let mut battle: Battle = "Immune System:
+10 units each with 100 hit points with an attack that does 1 fire damage at initiative 1
+
+Infection:
+10 units each with 100 hit points (immune to fire) with an attack that does 1 cold damage at initiative 2"
+            .parse()
+            .unwrap();
+        assert_eq!(battle.fight(), Outcome::Stalemate);
+        assert_eq!(battle.smallest_winning_boost(), None);
+    }
+
+    #[test]
+    fn hopeless() {
+        // The infection can't be hurt at all, and would take a huge boost to wipe out otherwise.
+        let battle: Battle = "Immune System:
+10 units each with 100 hit points with an attack that does 1 fire damage at initiative 1
+
+Infection:
+1000000 units each with 1000 hit points (immune to fire) with an attack that does 1 cold damage at initiative 2"
+            .parse()
+            .unwrap();
+        assert_eq!(battle.smallest_winning_boost(), None);
+
+        // The infection strikes first and wipes out the immune system, however hard it hits back.
+        let battle: Battle = "Immune System:
+1 units each with 1 hit points with an attack that does 1 fire damage at initiative 1
+
+Infection:
+1000 units each with 10 hit points with an attack that does 10 cold damage at initiative 2"
+            .parse()
+            .unwrap();
+        assert_eq!(battle.smallest_winning_boost(), None);
+    }
+}
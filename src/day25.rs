@@ -0,0 +1,259 @@
+use crate::geometry::{Coordinates, PointN};
+use crate::runner::Puzzle;
+use std::collections::HashMap;
+
+// Points closer than this (or exactly this far apart) are in the same constellation.
+const MAX_DISTANCE: i32 = 3;
+
+fn parse_points(s: &str) -> Result<Vec<PointN>, String> {
+    let points: Vec<PointN> = s
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.parse())
+        .collect::<Result<_, _>>()?;
+    if let Some(first) = points.first() {
+        if let Some(p) = points.iter().find(|p| p.0.len() != first.0.len()) {
+            return Err(format!(
+                "expected {} coordinates, found {:?}",
+                first.0.len(),
+                p.0
+            ));
+        }
+    }
+    Ok(points)
+}
+
+struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(n: usize) -> UnionFind {
+        UnionFind {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+        }
+    }
+
+    fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        // Point everything along the way straight at the root, so the next lookup is quick.
+        let mut cur = x;
+        while self.parent[cur] != root {
+            let next = self.parent[cur];
+            self.parent[cur] = root;
+            cur = next;
+        }
+        root
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        let (big, small) = if self.size[a] >= self.size[b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        self.parent[small] = big;
+        self.size[big] += self.size[small];
+    }
+}
+
+// Which bucket a point falls in. Buckets are cubes just wider than `MAX_DISTANCE`, so every
+// point within range of a point is in the same bucket or a neighbouring one.
+fn bucket(p: &PointN) -> Vec<i32> {
+    let side = MAX_DISTANCE + 1;
+    p.0.iter()
+        .map(|&c| if c >= 0 { c / side } else { (c + 1) / side - 1 })
+        .collect()
+}
+
+// Every offset from a bucket to one of its neighbours (including itself) in `dims` dimensions.
+fn offsets(dims: usize) -> Vec<Vec<i32>> {
+    let mut offsets = vec![Vec::new()];
+    for _ in 0..dims {
+        offsets = offsets
+            .into_iter()
+            .flat_map(|offset: Vec<i32>| {
+                (-1..=1).map(move |d| {
+                    let mut next = offset.clone();
+                    next.push(d);
+                    next
+                })
+            })
+            .collect();
+    }
+    offsets
+}
+
+// Groups the points into constellations, as lists of indices into `points`. Each constellation
+// is in increasing order, and the constellations are ordered by their first point.
+fn constellations(points: &[PointN]) -> Vec<Vec<usize>> {
+    let mut buckets: HashMap<Vec<i32>, Vec<usize>> = HashMap::new();
+    for (i, p) in points.iter().enumerate() {
+        buckets.entry(bucket(p)).or_default().push(i);
+    }
+
+    let dims = points.first().map_or(0, |p| p.0.len());
+    let offsets = offsets(dims);
+    let mut uf = UnionFind::new(points.len());
+    for (i, p) in points.iter().enumerate() {
+        let home = bucket(p);
+        for offset in &offsets {
+            let neighbor: Vec<i32> = home.iter().zip(offset).map(|(a, b)| a + b).collect();
+            for &j in buckets.get(&neighbor).into_iter().flatten() {
+                if j < i && p.manhattan(&points[j]) <= MAX_DISTANCE as usize {
+                    uf.union(i, j);
+                }
+            }
+        }
+    }
+
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut group_of_root: HashMap<usize, usize> = HashMap::new();
+    for i in 0..points.len() {
+        let root = uf.find(i);
+        let idx = *group_of_root.entry(root).or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        groups[idx].push(i);
+    }
+    groups
+}
+
+pub(crate) struct Day25;
+
+impl Puzzle for Day25 {
+    type Input = Vec<PointN>;
+
+    // Day 25 only has the one puzzle.
+    const PARTS: u32 = 1;
+
+    fn parse(s: &str) -> Result<Vec<PointN>, String> {
+        parse_points(s)
+    }
+
+    fn part1(points: &Vec<PointN>) -> Result<String, String> {
+        Ok(constellations(points).len().to_string())
+    }
+
+    fn part2(_: &Vec<PointN>) -> Result<String, String> {
+        Err(String::from("there is no second part on day 25"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::Lcg;
+
+    fn count(s: &str) -> usize {
+        constellations(&parse_points(s).unwrap()).len()
+    }
+
+    #[test]
+    fn smoke() {
+        let points = parse_points(
+            "0,0,0,0
+3,0,0,0
+0,3,0,0
+0,0,3,0
+0,0,0,3
+0,0,0,6
+9,0,0,0
+12,0,0,0",
+        )
+        .unwrap();
+        assert_eq!(
+            constellations(&points),
+            vec![vec![0, 1, 2, 3, 4, 5], vec![6, 7]]
+        );
+        assert!(parse_points("1,2,3,4\n1,2,3").is_err());
+        assert!(parse_points("1,2,x,4").is_err());
+    }
+
+    #[test]
+    fn part1() {
+        assert_eq!(
+            count(
+                "-1,2,2,0
+0,0,2,-2
+0,0,0,-2
+-1,2,0,0
+-2,-2,-2,2
+3,0,2,-1
+-1,3,2,2
+-1,0,-1,0
+0,2,1,-2
+3,0,0,0"
+            ),
+            4
+        );
+        assert_eq!(
+            count(
+                "1,-1,0,1
+2,0,-1,0
+3,2,-1,0
+0,0,3,1
+0,0,-1,-1
+2,3,-2,0
+-2,2,0,0
+2,-2,0,-1
+1,-1,0,-1
+3,2,0,2"
+            ),
+            3
+        );
+        assert_eq!(
+            count(
+                "1,-1,-1,-2
+-2,-2,0,1
+0,2,1,3
+-2,3,-2,1
+0,2,3,-2
+-1,-1,1,-2
+0,-2,-1,0
+-2,2,3,-1
+1,2,2,0
+-1,-2,0,-2"
+            ),
+            8
+        );
+    }
+
+    #[test]
+    fn bucketing() {
+        assert_eq!(bucket(&PointN(vec![0, 3, 4, -1])), vec![0, 0, 1, -1]);
+        assert_eq!(bucket(&PointN(vec![-4, -5, 7, 8])), vec![-1, -2, 1, 2]);
+        assert_eq!(offsets(4).len(), 81);
+
+        // Agrees with comparing every pair of points directly.
+        let mut rng = Lcg::new(25);
+        let points: Vec<PointN> = (0..3000)
+            .map(|_| PointN((0..4).map(|_| rng.below(61) as i32 - 30).collect()))
+            .collect();
+        let mut uf = UnionFind::new(points.len());
+        for i in 0..points.len() {
+            for j in 0..i {
+                if points[i].manhattan(&points[j]) <= MAX_DISTANCE as usize {
+                    uf.union(i, j);
+                }
+            }
+        }
+        let groups = constellations(&points);
+        assert!(groups.len() > 1 && groups.len() < points.len());
+        for group in groups {
+            let root = uf.find(group[0]);
+            let expected: Vec<usize> = (0..points.len()).filter(|&i| uf.find(i) == root).collect();
+            assert_eq!(group, expected);
+        }
+    }
+}
@@ -1,84 +1,80 @@
-use lazy_static::lazy_static;
-use regex::Regex;
-use std::collections::HashMap;
-use std::str::FromStr;
+use crate::from_pattern;
+use crate::geometry::{rectangle, BoundingBox, Point, Points};
+use crate::grid::Grid;
+use crate::runner::{parse_lines, Puzzle};
 
-#[derive(Debug, Clone)]
-struct Rectangle {
-    id: u32,
-    x: u32,
-    y: u32,
-    w: u32,
-    h: u32,
-}
-
-lazy_static! {
+from_pattern! {
     // Example: #37 @ 801,484: 22x28
-    static ref PATTERN: Regex = Regex::new(r"#(?P<id>\d+) @ (?P<x>\d+),(?P<y>\d+): (?P<w>\d+)x(?P<h>\d+)").unwrap();
+    #[pattern = r"#(?P<id>\d+) @ (?P<x>\d+),(?P<y>\d+): (?P<w>\d+)x(?P<h>\d+)"]
+    #[derive(Debug, Clone)]
+    pub(crate) struct Rectangle {
+        pub(crate) id: u32,
+        pub(crate) x: u32,
+        pub(crate) y: u32,
+        pub(crate) w: u32,
+        pub(crate) h: u32,
+    }
 }
-impl FromStr for Rectangle {
-    type Err = ();
 
-    fn from_str(s: &str) -> Result<Self, <Self as FromStr>::Err> {
-        let cap = PATTERN.captures(s).ok_or(())?;
-        Ok(Rectangle {
-            id: cap["id"].parse().map_err(|_| ())?,
-            x: cap["x"].parse().map_err(|_| ())?,
-            y: cap["y"].parse().map_err(|_| ())?,
-            w: cap["w"].parse().map_err(|_| ())?,
-            h: cap["h"].parse().map_err(|_| ())?,
-        })
+impl Rectangle {
+    pub(crate) fn points(&self) -> Points {
+        let (x, y) = (self.x as i32, self.y as i32);
+        rectangle(x..x + self.w as i32, y..y + self.h as i32)
     }
 }
 
-impl Rectangle {
-    fn points(&self) -> Points {
-        Points {
-            rect: self.clone(),
-            i: 0,
-            j: 0,
+fn coverage(rects: &[Rectangle]) -> Grid<usize> {
+    let corners: Vec<Point> = rects
+        .iter()
+        .filter(|rect| rect.w > 0 && rect.h > 0)
+        .flat_map(|rect| {
+            let (x, y) = (rect.x as i32, rect.y as i32);
+            vec![
+                Point(x, y),
+                Point(x + rect.w as i32 - 1, y + rect.h as i32 - 1),
+            ]
+        })
+        .collect();
+    let mut tally = match BoundingBox::from_points(&corners) {
+        Some(bounds) => Grid::from_bounds(&bounds, 0),
+        None => Grid::new(0, 0, 0),
+    };
+    for rect in rects {
+        for pt in rect.points() {
+            tally[pt] += 1;
         }
     }
+    tally
 }
 
-struct Points {
-    rect: Rectangle,
-    i: u32,
-    j: u32,
-}
+pub(crate) struct Day3;
 
-impl Iterator for Points {
-    type Item = (u32, u32);
+impl Puzzle for Day3 {
+    type Input = Vec<Rectangle>;
 
-    fn next(&mut self) -> Option<(u32, u32)> {
-        if self.i >= self.rect.h {
-            return None;
-        }
-        let item = (self.rect.x + self.j, self.rect.y + self.i);
-        if self.j + 1 < self.rect.w {
-            self.j += 1;
-        } else {
-            self.j = 0;
-            self.i += 1;
-        };
-        Some(item)
+    fn parse(s: &str) -> Result<Vec<Rectangle>, String> {
+        parse_lines(s)
     }
-}
 
-fn coverage(rects: &[Rectangle]) -> HashMap<(u32, u32), usize> {
-    let mut tally = HashMap::new();
-    for rect in rects {
-        for pt in rect.points() {
-            *tally.entry(pt).or_insert(0) += 1;
-        }
+    fn part1(rects: &Vec<Rectangle>) -> Result<String, String> {
+        let tally = coverage(rects);
+        Ok(tally.values().filter(|&&c| c >= 2).count().to_string())
+    }
+
+    fn part2(rects: &Vec<Rectangle>) -> Result<String, String> {
+        let tally = coverage(rects);
+        rects
+            .iter()
+            .find(|rect| rect.points().all(|pt| tally[pt] <= 1))
+            .map(|rect| rect.id.to_string())
+            .ok_or_else(|| String::from("every rectangle overlaps another"))
     }
-    tally
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use std::fs;
+    use crate::runner;
 
     #[test]
     fn points_smoke_test() {
@@ -92,35 +88,24 @@ mod test {
         assert_eq!(r.points().count(), 3 * 2);
         assert_eq!(
             r.points().collect::<Vec<_>>(),
-            vec![(1, 2), (2, 2), (3, 2), (1, 3), (2, 3), (3, 3)]
+            vec![
+                Point(1, 2),
+                Point(2, 2),
+                Point(3, 2),
+                Point(1, 3),
+                Point(2, 3),
+                Point(3, 3)
+            ]
         );
     }
 
-    lazy_static! {
-        static ref INPUTS: Vec<Rectangle> = {
-            let contents = fs::read_to_string("data/day3/input").expect("read input file");
-            contents
-                .lines()
-                .map(|s| s.parse())
-                .collect::<Result<Vec<Rectangle>, _>>()
-                .expect("parse inputs")
-        };
-    }
-
     #[test]
     fn part1() {
-        let tally = coverage(&INPUTS);
-        let x2 = tally.values().filter(|&&c| c >= 2).count();
-        assert_eq!(x2, 101196);
+        runner::check_answer::<Day3>(3, 1);
     }
 
     #[test]
     fn part2() {
-        let tally = coverage(&INPUTS);
-        let no_overlaps = INPUTS
-            .iter()
-            .filter(|&rect| rect.points().all(|pt| *tally.get(&pt).unwrap_or(&0) <= 1))
-            .next();
-        assert_eq!(no_overlaps.expect("find a no-overlap rectangle").id, 243);
+        runner::check_answer::<Day3>(3, 2);
     }
 }
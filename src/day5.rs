@@ -1,14 +1,12 @@
+use crate::runner::Puzzle;
+
 fn compact_length(mut chars: Vec<u8>) -> usize {
     let mut i = 0;
     while i + 1 < chars.len() {
-        if chars[i].to_ascii_lowercase() == chars[i + 1].to_ascii_lowercase()
-            && chars[i] != chars[i + 1]
-        {
+        if chars[i].eq_ignore_ascii_case(&chars[i + 1]) && chars[i] != chars[i + 1] {
             chars.remove(i);
             chars.remove(i);
-            if i > 0 {
-                i -= 1;
-            }
+            i = i.saturating_sub(1);
         } else {
             i += 1;
         }
@@ -16,35 +14,44 @@ fn compact_length(mut chars: Vec<u8>) -> usize {
     chars.len()
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use lazy_static::lazy_static;
-    use std::fs;
-
-    lazy_static! {
-        static ref INPUT: Vec<u8> = {
-            let mut raw = fs::read("data/day5/input").expect("read input file");
-            raw.retain(|&b| (b'a' <= b && b <= b'z') || (b'A' <= b && b <= b'Z'));
-            raw
-        };
+pub(crate) struct Day5;
+
+impl Puzzle for Day5 {
+    type Input = Vec<u8>;
+
+    fn parse(s: &str) -> Result<Vec<u8>, String> {
+        Ok(s.trim().bytes().collect())
     }
 
-    #[test]
-    fn part1() {
-        assert_eq!(compact_length(INPUT.clone()), 11118);
+    fn part1(polymer: &Vec<u8>) -> Result<String, String> {
+        Ok(compact_length(polymer.clone()).to_string())
     }
 
-    #[test]
-    fn part2() {
+    fn part2(polymer: &Vec<u8>) -> Result<String, String> {
         let shortest = (b'A'..=b'Z')
             .map(|r| {
-                let mut input = INPUT.clone();
-                input.drain_filter(|&mut b| b == r || b == r.to_ascii_lowercase());
+                let mut input = polymer.clone();
+                input.retain(|&b| b != r && b != r.to_ascii_lowercase());
                 compact_length(input)
             })
             .min()
             .unwrap();
-        assert_eq!(shortest, 6948);
+        Ok(shortest.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::runner;
+
+    #[test]
+    fn part1() {
+        runner::check_answer::<Day5>(5, 1);
+    }
+
+    #[test]
+    fn part2() {
+        runner::check_answer::<Day5>(5, 2);
     }
 }
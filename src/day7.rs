@@ -1,64 +1,38 @@
-use lazy_static::lazy_static;
-use regex::Regex;
+use crate::from_pattern;
+use crate::parse;
+use crate::runner::{parse_lines, Puzzle};
+use crate::search::{self, TopologicalOrder};
 use std::cmp::Reverse;
 use std::collections::{BinaryHeap, HashMap};
-use std::str::FromStr;
 
-struct Dependency {
-    before: u8,
-    after: u8,
-}
-lazy_static! {
+from_pattern! {
     // Example: Step B must be finished before step C can begin.
-    static ref PATTERN: Regex = Regex::new(r"Step (?P<before>[[:alpha:]]) must be finished before step (?P<after>[[:alpha:]]) can begin.").unwrap();
-}
-impl FromStr for Dependency {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, String> {
-        let cap = PATTERN
-            .captures(s)
-            .ok_or(format!("could not parse {}", s))?;
-        Ok(Dependency {
-            before: cap["before"].as_bytes()[0],
-            after: cap["after"].as_bytes()[0],
-        })
+    #[pattern = r"Step (?P<before>[[:alpha:]]) must be finished before step (?P<after>[[:alpha:]]) can begin."]
+    pub(crate) struct Dependency {
+        before: u8 = parse::byte,
+        after: u8 = parse::byte,
     }
 }
 
-fn topo_sort(dependencies: &[Dependency]) -> Vec<u8> {
+// The steps that have to wait for each step.
+fn graph(dependencies: &[Dependency]) -> HashMap<u8, Vec<u8>> {
     let mut graph: HashMap<u8, Vec<u8>> = HashMap::new();
-    let mut in_degree: HashMap<u8, usize> = HashMap::new();
     for dep in dependencies {
-        // Add the edge to the graph.
         graph.entry(dep.before).or_default().push(dep.after);
-        // Add up the in-degrees
-        in_degree.entry(dep.before).or_default();
-        *in_degree.entry(dep.after).or_default() += 1;
-    }
-
-    let mut frontier: BinaryHeap<Reverse<u8>> = BinaryHeap::new();
-    for (&id, &count) in in_degree.iter() {
-        if count == 0 {
-            frontier.push(Reverse(id));
-        }
+        graph.entry(dep.after).or_default();
     }
+    graph
+}
 
-    let mut ordered = Vec::new();
-    while let Some(Reverse(cur)) = frontier.pop() {
-        ordered.push(cur);
-        for targets in graph.get(&cur) {
-            for &target in targets {
-                let count = in_degree.get_mut(&target).unwrap();
-                *count -= 1;
-                if *count == 0 {
-                    frontier.push(Reverse(target));
-                }
-            }
-        }
-    }
+fn order(dependencies: &[Dependency]) -> TopologicalOrder<u8> {
+    let graph = graph(dependencies);
+    TopologicalOrder::new(graph.keys().cloned(), |id| graph[id].clone())
+}
 
-    ordered
+// Every step in order, or `None` if some steps wait on each other in a cycle.
+fn topo_sort(dependencies: &[Dependency]) -> Option<Vec<u8>> {
+    let graph = graph(dependencies);
+    search::topological_sort(graph.keys().cloned(), |id| graph[id].clone())
 }
 
 #[derive(Ord, PartialOrd, Eq, PartialEq, Debug, Copy, Clone)]
@@ -70,82 +44,63 @@ fn timed_topo_sort<F>(dependencies: &[Dependency], mut workers: usize, duration:
 where
     F: Fn(u8) -> usize,
 {
-    let mut graph: HashMap<u8, Vec<u8>> = HashMap::new();
-    let mut in_degree: HashMap<u8, usize> = HashMap::new();
-    for dep in dependencies {
-        // Add the edge to the graph.
-        graph.entry(dep.before).or_default().push(dep.after);
-        // Add up the in-degrees
-        in_degree.entry(dep.before).or_default();
-        *in_degree.entry(dep.after).or_default() += 1;
-    }
-
-    let mut frontier: BinaryHeap<Reverse<u8>> = BinaryHeap::new();
-    for (&id, &count) in in_degree.iter() {
-        if count == 0 {
-            frontier.push(Reverse(id));
-        }
-    }
-
+    let mut order = order(dependencies);
     let mut events: BinaryHeap<Reverse<Event>> = BinaryHeap::new();
     let mut ordered: Vec<Event> = Vec::new();
 
-    while workers > 0 {
-        if let Some(Reverse(id)) = frontier.pop() {
-            workers -= 1;
-            events.push(Reverse(Event {
-                id,
-                t: duration(id),
-            }));
-        } else {
-            break;
-        }
-    }
-
-    while let Some(Reverse(evt)) = events.pop() {
-        workers += 1;
-        ordered.push(evt);
-        for targets in graph.get(&evt.id) {
-            for &target in targets {
-                let count = in_degree.get_mut(&target).unwrap();
-                *count -= 1;
-                if *count == 0 {
-                    frontier.push(Reverse(target));
-                }
-            }
-        }
-
+    let mut now = 0;
+    loop {
         while workers > 0 {
-            if let Some(Reverse(id)) = frontier.pop() {
+            if let Some(id) = order.next_ready() {
                 workers -= 1;
                 events.push(Reverse(Event {
                     id,
-                    t: evt.t + duration(id),
+                    t: now + duration(id),
                 }));
             } else {
                 break;
             }
         }
+
+        match events.pop() {
+            Some(Reverse(evt)) => {
+                workers += 1;
+                now = evt.t;
+                order.complete(&evt.id);
+                ordered.push(evt);
+            }
+            None => break,
+        }
     }
 
     ordered
 }
 
+pub(crate) struct Day7;
+
+impl Puzzle for Day7 {
+    type Input = Vec<Dependency>;
+
+    fn parse(s: &str) -> Result<Vec<Dependency>, String> {
+        parse_lines(s)
+    }
+
+    fn part1(deps: &Vec<Dependency>) -> Result<String, String> {
+        let order = topo_sort(deps)
+            .ok_or_else(|| String::from("the steps wait on each other in a cycle"))?;
+        String::from_utf8(order).map_err(|e| e.to_string())
+    }
+
+    fn part2(deps: &Vec<Dependency>) -> Result<String, String> {
+        let events = timed_topo_sort(deps, 5, |id| id as usize - 4);
+        Ok(events.last().map_or(0, |e| e.t).to_string())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use lazy_static::lazy_static;
-    use std::fs;
-    use std::io::BufRead;
-
-    lazy_static! {
-        static ref INPUT: Vec<Dependency> = {
-            let raw = fs::read("data/day7/input").expect("read input file");
-            raw.lines()
-                .map(|line| line.unwrap().parse().unwrap())
-                .collect()
-        };
-    }
+    use crate::runner;
 
     #[test]
     fn smoke() {
@@ -170,16 +125,11 @@ mod test {
 
     #[test]
     fn part1() {
-        let order = topo_sort(&INPUT);
-        assert_eq!(
-            String::from_utf8(order).unwrap(),
-            "IOFSJQDUWAPXELNVYZMHTBCRGK"
-        );
+        runner::check_answer::<Day7>(7, 1);
     }
 
     #[test]
     fn part2() {
-        let events = timed_topo_sort(&INPUT, 5, |id| id as usize - 4);
-        assert_eq!(events.last().unwrap().t, 931);
+        runner::check_answer::<Day7>(7, 2);
     }
 }
@@ -1,4 +1,6 @@
-struct Node {
+use crate::runner::Puzzle;
+
+pub(crate) struct Node {
     metadata: Vec<usize>,
     children: Vec<Node>,
 }
@@ -38,18 +40,33 @@ impl Node {
     }
 }
 
+pub(crate) struct Day8;
+
+impl Puzzle for Day8 {
+    type Input = Node;
+
+    fn parse(s: &str) -> Result<Node, String> {
+        let numbers = s
+            .split_whitespace()
+            .map(|w| w.parse().map_err(|_| format!("could not parse {}", w)))
+            .collect::<Result<Vec<usize>, String>>()?;
+        Node::from_iter(&mut numbers.into_iter())
+            .ok_or_else(|| String::from("the tree is cut short"))
+    }
+
+    fn part1(root: &Node) -> Result<String, String> {
+        Ok(root.simple_sum().to_string())
+    }
+
+    fn part2(root: &Node) -> Result<String, String> {
+        Ok(root.complex_sum().to_string())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use lazy_static::lazy_static;
-    use std::fs;
-
-    lazy_static! {
-        static ref INPUT: Vec<usize> = {
-            let raw = fs::read_to_string("data/day8/input").expect("read input file");
-            raw.split_whitespace().map(|w| w.parse().unwrap()).collect()
-        };
-    }
+    use crate::runner;
 
     #[test]
     fn smoke() {
@@ -62,15 +79,11 @@ mod test {
 
     #[test]
     fn part1() {
-        let mut iter = INPUT.iter().cloned();
-        let node = Node::from_iter(&mut iter).unwrap();
-        assert_eq!(node.simple_sum(), 42254);
+        runner::check_answer::<Day8>(8, 1);
     }
 
     #[test]
     fn part2() {
-        let mut iter = INPUT.iter().cloned();
-        let node = Node::from_iter(&mut iter).unwrap();
-        assert_eq!(node.complex_sum(), 25007);
+        runner::check_answer::<Day8>(8, 2);
     }
 }
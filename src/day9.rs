@@ -1,3 +1,5 @@
+use crate::from_pattern;
+use crate::runner::Puzzle;
 use std::collections::VecDeque;
 
 fn play(num_players: usize, last_marble: usize) -> Vec<usize> {
@@ -19,9 +21,39 @@ fn play(num_players: usize, last_marble: usize) -> Vec<usize> {
     scores
 }
 
+from_pattern! {
+    // Example: 411 players; last marble is worth 72059 points
+    #[pattern = r"^(?P<players>\d+) players; last marble is worth (?P<last_marble>\d+) points"]
+    pub(crate) struct Game {
+        players: usize,
+        last_marble: usize,
+    }
+}
+
+pub(crate) struct Day9;
+
+impl Puzzle for Day9 {
+    type Input = Game;
+
+    fn parse(s: &str) -> Result<Game, String> {
+        s.trim().parse().map_err(|e| format!("{}", e))
+    }
+
+    fn part1(game: &Game) -> Result<String, String> {
+        let scores = play(game.players, game.last_marble);
+        Ok(scores.into_iter().max().unwrap_or(0).to_string())
+    }
+
+    fn part2(game: &Game) -> Result<String, String> {
+        let scores = play(game.players, 100 * game.last_marble);
+        Ok(scores.into_iter().max().unwrap_or(0).to_string())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::runner;
 
     #[test]
     fn smoke() {
@@ -35,14 +67,11 @@ mod test {
 
     #[test]
     fn part1() {
-        assert_eq!(play(411, 72059).into_iter().max().unwrap(), 429943);
+        runner::check_answer::<Day9>(9, 1);
     }
 
     #[test]
     fn part2() {
-        assert_eq!(
-            play(411, 72059 * 100).into_iter().max().unwrap(),
-            3615691746
-        );
+        runner::check_answer::<Day9>(9, 2);
     }
 }
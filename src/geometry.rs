@@ -0,0 +1,442 @@
+use std::convert::TryFrom;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::{Add, Mul, Neg, Range, Sub};
+use std::str::FromStr;
+
+// Anything with an integer coordinate along each of some number of axes.
+pub trait Coordinates: Clone + Eq + Hash + Debug {
+    fn dims(&self) -> usize;
+
+    fn axis(&self, i: usize) -> i64;
+
+    // A point with the same number of dimensions, with `f(i, self.axis(i))` along each axis.
+    fn map_axes<F>(&self, f: F) -> Self
+    where
+        F: FnMut(usize, i64) -> i64;
+
+    // Both points need the same number of dimensions, for this and `chebyshev`.
+    fn manhattan(&self, other: &Self) -> usize {
+        (0..self.dims())
+            .map(|i| (self.axis(i) - other.axis(i)).unsigned_abs() as usize)
+            .sum()
+    }
+
+    fn chebyshev(&self, other: &Self) -> usize {
+        (0..self.dims())
+            .map(|i| (self.axis(i) - other.axis(i)).unsigned_abs() as usize)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Hash, Eq, PartialEq, Copy, Clone, Debug)]
+pub struct Point(pub i32, pub i32);
+
+#[derive(Hash, Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Debug)]
+pub struct Point3(pub i64, pub i64, pub i64);
+
+#[derive(Hash, Eq, PartialEq, Clone, Debug)]
+pub struct PointN(pub Vec<i32>);
+
+// Points and `PointN`s keep their coordinates as `i32`s. Arithmetic that leaves that range is a
+// bug, like any other overflow, rather than something to wrap around.
+fn narrow(c: i64) -> i32 {
+    i32::try_from(c).unwrap_or_else(|_| panic!("coordinate {} is out of range", c))
+}
+
+// `Point3`s work with `i64`s directly, so the arithmetic itself has to be checked instead.
+fn checked(result: Option<i64>, a: i64, op: &str, b: i64) -> i64 {
+    result.unwrap_or_else(|| panic!("coordinate {} {} {} is out of range", a, op, b))
+}
+
+impl Coordinates for Point {
+    fn dims(&self) -> usize {
+        2
+    }
+
+    fn axis(&self, i: usize) -> i64 {
+        match i {
+            0 => i64::from(self.0),
+            1 => i64::from(self.1),
+            _ => panic!("no axis {} in {:?}", i, self),
+        }
+    }
+
+    fn map_axes<F>(&self, mut f: F) -> Point
+    where
+        F: FnMut(usize, i64) -> i64,
+    {
+        Point(
+            narrow(f(0, i64::from(self.0))),
+            narrow(f(1, i64::from(self.1))),
+        )
+    }
+}
+
+impl Coordinates for Point3 {
+    fn dims(&self) -> usize {
+        3
+    }
+
+    fn axis(&self, i: usize) -> i64 {
+        match i {
+            0 => self.0,
+            1 => self.1,
+            2 => self.2,
+            _ => panic!("no axis {} in {:?}", i, self),
+        }
+    }
+
+    fn map_axes<F>(&self, mut f: F) -> Point3
+    where
+        F: FnMut(usize, i64) -> i64,
+    {
+        Point3(f(0, self.0), f(1, self.1), f(2, self.2))
+    }
+}
+
+impl Coordinates for PointN {
+    fn dims(&self) -> usize {
+        self.0.len()
+    }
+
+    fn axis(&self, i: usize) -> i64 {
+        i64::from(self.0[i])
+    }
+
+    fn map_axes<F>(&self, mut f: F) -> PointN
+    where
+        F: FnMut(usize, i64) -> i64,
+    {
+        PointN(
+            self.0
+                .iter()
+                .enumerate()
+                .map(|(i, &c)| narrow(f(i, i64::from(c))))
+                .collect(),
+        )
+    }
+}
+
+// Points double as vectors: they add and subtract axis by axis, and scale by a constant.
+macro_rules! vector_ops {
+    ($point:ident, $scalar:ty) => {
+        impl Add for $point {
+            type Output = $point;
+
+            fn add(self, other: $point) -> $point {
+                self.map_axes(|i, c| {
+                    let d = other.axis(i);
+                    checked(c.checked_add(d), c, "+", d)
+                })
+            }
+        }
+
+        impl Sub for $point {
+            type Output = $point;
+
+            fn sub(self, other: $point) -> $point {
+                self.map_axes(|i, c| {
+                    let d = other.axis(i);
+                    checked(c.checked_sub(d), c, "-", d)
+                })
+            }
+        }
+
+        impl Neg for $point {
+            type Output = $point;
+
+            fn neg(self) -> $point {
+                self.map_axes(|_, c| checked(c.checked_neg(), 0, "-", c))
+            }
+        }
+
+        impl Mul<$scalar> for $point {
+            type Output = $point;
+
+            fn mul(self, k: $scalar) -> $point {
+                self.map_axes(|_, c| checked(c.checked_mul(k as i64), c, "*", k as i64))
+            }
+        }
+    };
+}
+
+vector_ops!(Point, i32);
+vector_ops!(Point3, i64);
+vector_ops!(PointN, i32);
+
+impl Point {
+    // The four points one step away, in the order +x, +y, -x, -y.
+    pub fn neighbors(&self) -> Vec<Point> {
+        vec![
+            Point(self.0 + 1, self.1),
+            Point(self.0, self.1 + 1),
+            Point(self.0 - 1, self.1),
+            Point(self.0, self.1 - 1),
+        ]
+    }
+}
+
+// Comma-separated coordinates, with any amount of whitespace around each one.
+fn parse_axes(s: &str, dims: Option<usize>) -> Result<Vec<i64>, String> {
+    let axes: Vec<i64> = s
+        .trim()
+        .split(',')
+        .map(|c| c.trim().parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("could not parse {}", s))?;
+    match dims {
+        Some(dims) if axes.len() != dims => Err(format!(
+            "expected {} coordinates, found {} in {}",
+            dims,
+            axes.len(),
+            s
+        )),
+        _ => Ok(axes),
+    }
+}
+
+// Example: 13, 94
+impl FromStr for Point {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Point, String> {
+        let axes = parse_axes(s, Some(2))?;
+        let axis = |c: i64| i32::try_from(c).map_err(|_| format!("{} is out of range in {}", c, s));
+        Ok(Point(axis(axes[0])?, axis(axes[1])?))
+    }
+}
+
+// Example: 1,-2,3
+impl FromStr for Point3 {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Point3, String> {
+        let axes = parse_axes(s, Some(3))?;
+        Ok(Point3(axes[0], axes[1], axes[2]))
+    }
+}
+
+// Example: 0,-3,2,2
+impl FromStr for PointN {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<PointN, String> {
+        let axes = parse_axes(s, None)?;
+        let axes: Result<Vec<i32>, String> = axes
+            .into_iter()
+            .map(|c| i32::try_from(c).map_err(|_| format!("{} is out of range in {}", c, s)))
+            .collect();
+        Ok(PointN(axes?))
+    }
+}
+
+// The smallest axis-aligned box containing some points. Both corners are inside the box.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BoundingBox<P> {
+    pub min: P,
+    pub max: P,
+}
+
+impl<P: Coordinates> BoundingBox<P> {
+    pub fn new(p: P) -> BoundingBox<P> {
+        BoundingBox {
+            min: p.clone(),
+            max: p,
+        }
+    }
+
+    // There's no bounding box around nothing at all.
+    pub fn from_points<'a, I>(points: I) -> Option<BoundingBox<P>>
+    where
+        I: IntoIterator<Item = &'a P>,
+        P: 'a,
+    {
+        let mut points = points.into_iter();
+        let mut bb = BoundingBox::new(points.next()?.clone());
+        for p in points {
+            bb.include(p);
+        }
+        Some(bb)
+    }
+
+    // Grows the box just enough to contain `p`.
+    pub fn include(&mut self, p: &P) {
+        self.min = self.min.map_axes(|i, c| c.min(p.axis(i)));
+        self.max = self.max.map_axes(|i, c| c.max(p.axis(i)));
+    }
+
+    pub fn union(&self, other: &BoundingBox<P>) -> BoundingBox<P> {
+        let mut bb = self.clone();
+        bb.include(&other.min);
+        bb.include(&other.max);
+        bb
+    }
+
+    pub fn contains(&self, p: &P) -> bool {
+        (0..p.dims()).all(|i| self.min.axis(i) <= p.axis(i) && p.axis(i) <= self.max.axis(i))
+    }
+
+    // The Manhattan distance from `p` to the nearest point in the box.
+    pub fn distance_to(&self, p: &P) -> usize {
+        (0..p.dims())
+            .map(|i| {
+                let below = (self.min.axis(i) - p.axis(i)).max(0);
+                let above = (p.axis(i) - self.max.axis(i)).max(0);
+                (below + above) as usize
+            })
+            .sum()
+    }
+}
+
+impl BoundingBox<Point> {
+    pub fn width(&self) -> usize {
+        (self.max.0 - self.min.0) as usize + 1
+    }
+
+    pub fn height(&self) -> usize {
+        (self.max.1 - self.min.1) as usize + 1
+    }
+
+    pub fn points(&self) -> Points {
+        rectangle(self.min.0..self.max.0 + 1, self.min.1..self.max.1 + 1)
+    }
+}
+
+// Every point in the half-open rectangle `xs` x `ys`, a row at a time.
+pub fn rectangle(xs: Range<i32>, ys: Range<i32>) -> Points {
+    let next = if xs.start < xs.end && ys.start < ys.end {
+        Some(Point(xs.start, ys.start))
+    } else {
+        None
+    };
+    Points { xs, ys, next }
+}
+
+pub struct Points {
+    xs: Range<i32>,
+    ys: Range<i32>,
+    next: Option<Point>,
+}
+
+impl Iterator for Points {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Point> {
+        let cur = self.next?;
+        self.next = if cur.0 + 1 < self.xs.end {
+            Some(Point(cur.0 + 1, cur.1))
+        } else if cur.1 + 1 < self.ys.end {
+            Some(Point(self.xs.start, cur.1 + 1))
+        } else {
+            None
+        };
+        Some(cur)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn smoke() {
+        assert_eq!(Point(1, 2) + Point(3, -4), Point(4, -2));
+        assert_eq!(Point(1, 2) - Point(3, -4), Point(-2, 6));
+        assert_eq!(-Point3(1, -2, 3), Point3(-1, 2, -3));
+        assert_eq!(PointN(vec![1, 2, 3, 4]) * 3, PointN(vec![3, 6, 9, 12]));
+
+        assert_eq!(Point(1, 2).manhattan(&Point(-2, 6)), 7);
+        assert_eq!(Point(1, 2).chebyshev(&Point(-2, 6)), 4);
+        assert_eq!(
+            Point3(300_000_000, 0, 0).manhattan(&Point3(-300_000_000, 1, -1)),
+            600_000_002
+        );
+        assert_eq!(
+            PointN(vec![0, 0, 0, 0]).manhattan(&PointN(vec![1, -1, 1, -1])),
+            4
+        );
+
+        assert_eq!("13, 94".parse(), Ok(Point(13, 94)));
+        assert_eq!("1,-2,3".parse(), Ok(Point3(1, -2, 3)));
+        assert_eq!("0,-3,2,2".parse(), Ok(PointN(vec![0, -3, 2, 2])));
+        assert!("1,2,3".parse::<Point>().is_err());
+        assert!("1,x".parse::<Point>().is_err());
+        assert!("1,3000000000".parse::<Point>().is_err());
+        assert!("0,-3000000000,2,2".parse::<PointN>().is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn overflow() {
+        let _ = Point(i32::MAX, 0) + Point(1, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn overflow_3d() {
+        let _ = Point3(0, i64::MAX / 2 + 1, 0) * 2;
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn negate_3d() {
+        let _ = -Point3(0, 0, i64::MIN);
+    }
+
+    #[test]
+    fn bounding_box() {
+        let points = vec![Point(1, 5), Point(-2, 3), Point(4, 4)];
+        let bb = BoundingBox::from_points(&points).unwrap();
+        assert_eq!(
+            bb,
+            BoundingBox {
+                min: Point(-2, 3),
+                max: Point(4, 5),
+            }
+        );
+        assert_eq!((bb.width(), bb.height()), (7, 3));
+        assert!(bb.contains(&Point(0, 4)));
+        assert!(!bb.contains(&Point(0, 6)));
+        assert_eq!(bb.distance_to(&Point(0, 4)), 0);
+        assert_eq!(bb.distance_to(&Point(6, 0)), 5);
+        assert_eq!(BoundingBox::<Point>::from_points(&[]), None);
+
+        let other = BoundingBox::new(Point3(0, 0, 10));
+        let cube = BoundingBox {
+            min: Point3(1, 1, 1),
+            max: Point3(2, 2, 2),
+        };
+        assert_eq!(
+            cube.union(&other),
+            BoundingBox {
+                min: Point3(0, 0, 1),
+                max: Point3(2, 2, 10),
+            }
+        );
+    }
+
+    #[test]
+    fn rectangles() {
+        assert_eq!(
+            rectangle(1..4, 2..4).collect::<Vec<_>>(),
+            vec![
+                Point(1, 2),
+                Point(2, 2),
+                Point(3, 2),
+                Point(1, 3),
+                Point(2, 3),
+                Point(3, 3),
+            ]
+        );
+        assert_eq!(rectangle(1..1, 2..4).count(), 0);
+        assert_eq!(rectangle(1..4, 2..2).count(), 0);
+        let bb = BoundingBox {
+            min: Point(-1, -1),
+            max: Point(1, 1),
+        };
+        assert_eq!(bb.points().count(), 9);
+        assert!(bb.points().all(|p| bb.contains(&p)));
+    }
+}
@@ -0,0 +1,281 @@
+use crate::geometry::{rectangle, BoundingBox, Point, Points};
+use std::fmt;
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+
+// A dense, row-major `width` x `height` grid. Cells are addressed by `Point`, with `origin` being
+// the top-left cell, so grids can cover negative coordinates too.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Grid<T> {
+    origin: Point,
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T> {
+        Grid {
+            origin: Point(0, 0),
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    // A grid covering exactly the points in `bounds`.
+    pub fn from_bounds(bounds: &BoundingBox<Point>, fill: T) -> Grid<T> {
+        Grid::new(bounds.width(), bounds.height(), fill).with_origin(bounds.min)
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn from_fn<F>(width: usize, height: usize, f: F) -> Grid<T>
+    where
+        F: FnMut(Point) -> T,
+    {
+        let cells = rectangle(0..width as i32, 0..height as i32)
+            .map(f)
+            .collect();
+        Grid {
+            origin: Point(0, 0),
+            width,
+            height,
+            cells,
+        }
+    }
+
+    // Moves the whole grid so that its top-left cell is at `origin`.
+    pub fn with_origin(mut self, origin: Point) -> Grid<T> {
+        self.origin = origin;
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn origin(&self) -> Point {
+        self.origin
+    }
+
+    // Every point in the grid, a row at a time.
+    pub fn points(&self) -> Points {
+        let Point(x0, y0) = self.origin;
+        rectangle(x0..x0 + self.width as i32, y0..y0 + self.height as i32)
+    }
+
+    fn index_of(&self, p: Point) -> Option<usize> {
+        let x = p.0 - self.origin.0;
+        let y = p.1 - self.origin.1;
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return None;
+        }
+        Some(self.width * y as usize + x as usize)
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        self.index_of(p).is_some()
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.index_of(p).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        match self.index_of(p) {
+            Some(i) => Some(&mut self.cells[i]),
+            None => None,
+        }
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    // The row of cells at height `y`.
+    pub fn row(&self, y: i32) -> &[T] {
+        let start = self
+            .index_of(Point(self.origin.0, y))
+            .unwrap_or_else(|| panic!("row {} is outside the grid", y));
+        &self.cells[start..start + self.width]
+    }
+
+    // The column of cells at `x`, from top to bottom.
+    pub fn column(&self, x: i32) -> impl Iterator<Item = &T> {
+        let start = self
+            .index_of(Point(x, self.origin.1))
+            .unwrap_or_else(|| panic!("column {} is outside the grid", x));
+        self.cells[start..].iter().step_by(self.width)
+    }
+
+    // The (up to four) points in the grid one step away from `p`.
+    pub fn neighbors4(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        p.neighbors().into_iter().filter(move |&n| self.contains(n))
+    }
+
+    // The (up to eight) points in the grid surrounding `p`, including diagonally.
+    pub fn neighbors8(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        rectangle(p.0 - 1..p.0 + 2, p.1 - 1..p.1 + 2).filter(move |&n| n != p && self.contains(n))
+    }
+
+    // Turns each cell into a single character, a row per line.
+    pub fn render<F>(&self, f: F) -> String
+    where
+        F: Fn(&T) -> char,
+    {
+        let mut buf = String::with_capacity((self.width + 1) * self.height);
+        for row in self.cells.chunks(self.width.max(1)) {
+            buf.extend(row.iter().map(&f));
+            buf.push('\n');
+        }
+        buf
+    }
+}
+
+impl Grid<u8> {
+    // Reads one row per line, and rejects any character that `valid` doesn't allow. Empty lines
+    // at the end are ignored, but a line of spaces is a row like any other.
+    pub fn parse<F>(s: &str, valid: F) -> Result<Grid<u8>, String>
+    where
+        F: Fn(u8) -> bool,
+    {
+        let mut rows: Vec<&str> = s.lines().collect();
+        while rows.last().is_some_and(|row| row.is_empty()) {
+            rows.pop();
+        }
+        let width = rows.first().map_or(0, |row| row.len());
+        let mut cells = Vec::with_capacity(width * rows.len());
+        for (y, row) in rows.iter().enumerate() {
+            if row.len() != width {
+                return Err(format!(
+                    "row {} has length {}, expected {}",
+                    y,
+                    row.len(),
+                    width
+                ));
+            }
+            for (x, b) in row.bytes().enumerate() {
+                if !valid(b) {
+                    return Err(format!("unexpected {:?} at {},{}", b as char, x, y));
+                }
+                cells.push(b);
+            }
+        }
+        Ok(Grid {
+            origin: Point(0, 0),
+            width,
+            height: rows.len(),
+            cells,
+        })
+    }
+}
+
+impl FromStr for Grid<u8> {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Grid<u8>, String> {
+        Grid::parse(s, |b| b.is_ascii_graphic() || b == b' ')
+    }
+}
+
+impl fmt::Display for Grid<u8> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.render(|&b| b as char))
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point) -> &T {
+        match self.index_of(p) {
+            Some(i) => &self.cells[i],
+            None => panic!("{:?} is outside the grid", p),
+        }
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut T {
+        match self.index_of(p) {
+            Some(i) => &mut self.cells[i],
+            None => panic!("{:?} is outside the grid", p),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn smoke() {
+        let mut grid = Grid::new(3, 2, 0).with_origin(Point(-1, 5));
+        grid[Point(-1, 5)] = 1;
+        grid[Point(1, 6)] = 2;
+        assert_eq!(grid.get(Point(0, 5)), Some(&0));
+        assert_eq!(grid.get(Point(2, 5)), None);
+        assert_eq!(grid.get(Point(-1, 4)), None);
+        assert_eq!(grid.row(5), &[1, 0, 0]);
+        assert_eq!(grid.row(6), &[0, 0, 2]);
+        assert_eq!(grid.column(1).cloned().collect::<Vec<_>>(), vec![0, 2]);
+        assert_eq!(
+            grid.iter().filter(|&(_, &v)| v > 0).collect::<Vec<_>>(),
+            vec![(Point(-1, 5), &1), (Point(1, 6), &2)]
+        );
+
+        let squares = Grid::from_fn(3, 3, |p| p.0 * p.1);
+        assert_eq!(squares[Point(2, 2)], 4);
+        assert_eq!(squares.values().sum::<i32>(), 9);
+    }
+
+    #[test]
+    fn neighbors() {
+        let grid = Grid::new(3, 3, ());
+        assert_eq!(grid.neighbors4(Point(1, 1)).count(), 4);
+        assert_eq!(
+            grid.neighbors4(Point(0, 0)).collect::<Vec<_>>(),
+            vec![Point(1, 0), Point(0, 1)]
+        );
+        assert_eq!(grid.neighbors8(Point(1, 1)).count(), 8);
+        assert_eq!(
+            grid.neighbors8(Point(2, 0)).collect::<Vec<_>>(),
+            vec![Point(1, 0), Point(1, 1), Point(2, 1)]
+        );
+    }
+
+    #[test]
+    fn ascii() {
+        let map = "#.#\n.##\n";
+        let grid: Grid<u8> = map.parse().unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[Point(1, 0)], b'.');
+        assert_eq!(grid.to_string(), map);
+        assert!("#.#\n.#".parse::<Grid<u8>>().is_err());
+        assert!(Grid::parse("#x#", |b| b == b'#' || b == b'.').is_err());
+    }
+
+    #[test]
+    fn blank_rows() {
+        let map = "# #\n   \n#  \n";
+        let grid: Grid<u8> = format!("{}\n\n", map).parse().unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 3));
+        assert_eq!(grid.row(1), b"   ");
+        assert_eq!(grid.to_string(), map);
+
+        // Only empty lines at the end are skipped.
+        assert_eq!(
+            "# #\n\n#  ".parse::<Grid<u8>>().err(),
+            Some(String::from("row 1 has length 0, expected 3"))
+        );
+    }
+}
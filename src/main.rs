@@ -0,0 +1,66 @@
+use advent_of_code_2018::runner::{self, Solution, Verdict};
+use std::env;
+use std::process;
+
+const USAGE: &str = "usage: advent-of-code-2018 [verify] [--parallel] [--json] [DAY...]";
+
+fn main() {
+    let mut verify = false;
+    let mut parallel = false;
+    let mut json = false;
+    let mut days: Vec<u32> = Vec::new();
+    for (i, arg) in env::args().skip(1).enumerate() {
+        match arg.as_str() {
+            "verify" if i == 0 => verify = true,
+            "--parallel" => parallel = true,
+            "--json" => json = true,
+            _ => match arg.parse() {
+                Ok(day) => days.push(day),
+                Err(_) => {
+                    eprintln!("{}", USAGE);
+                    process::exit(2);
+                }
+            },
+        }
+    }
+
+    // Timing runs use each day's usual input, while verifying checks every input there is. Days
+    // without an input are left out, unless they're asked for by name.
+    let jobs: Vec<(Solution, String)> = runner::solutions()
+        .into_iter()
+        .filter(|s| {
+            if days.is_empty() {
+                s.has_input()
+            } else {
+                days.contains(&s.day)
+            }
+        })
+        .flat_map(|s| {
+            let inputs = if verify {
+                s.inputs()
+            } else {
+                vec![s.input_path()]
+            };
+            inputs.into_iter().map(move |input| (s, input))
+        })
+        .collect();
+    let run = runner::run(&jobs, parallel);
+    if json {
+        print!("{}", run.json());
+    } else if verify {
+        print!("{}", run.verification());
+    } else {
+        print!("{}", run.table());
+    }
+
+    let failed = if verify {
+        run.reports
+            .iter()
+            .any(|r| matches!(r.verdict(), Verdict::Fail(_)))
+    } else {
+        run.reports.iter().any(|r| r.answer.is_err())
+    };
+    if failed {
+        process::exit(1);
+    }
+}
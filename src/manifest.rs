@@ -0,0 +1,187 @@
+use std::str::FromStr;
+
+// The known answers for one puzzle input, read from a TOML file next to it. Only the bits of TOML
+// that answers need are understood: `part1` and `part2` keys, whose values are integers, strings,
+// or multi-line strings (for answers like day 10's message). Either part can be left out.
+//
+//     # data/day1/input.toml
+//     part1 = 402
+//     part2 = 481
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct Answers {
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+impl Answers {
+    // Where the answers for the input at `input_path` live.
+    pub fn path(input_path: &str) -> String {
+        format!("{}.toml", input_path)
+    }
+
+    pub fn get(&self, part: u32) -> Option<&str> {
+        match part {
+            1 => self.part1.as_deref(),
+            2 => self.part2.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+// Whether `actual` is the `expected` answer. Multi-line answers are compared without any trailing
+// whitespace on each line, or blank lines around them.
+pub fn matches(expected: &str, actual: &str) -> bool {
+    normalize(expected) == normalize(actual)
+}
+
+fn normalize(s: &str) -> String {
+    let lines: Vec<&str> = s.lines().map(str::trim_end).collect();
+    lines.join("\n").trim_matches('\n').to_owned()
+}
+
+fn unescape(s: &str) -> Result<String, String> {
+    let mut buf = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            buf.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('"') => buf.push('"'),
+            Some('\\') => buf.push('\\'),
+            Some('n') => buf.push('\n'),
+            Some('t') => buf.push('\t'),
+            other => return Err(format!("unsupported escape \\{}", other.unwrap_or(' '))),
+        }
+    }
+    Ok(buf)
+}
+
+// Anything after the value has to be a comment.
+fn check_rest(rest: &str) -> Result<(), String> {
+    let rest = rest.trim();
+    if rest.is_empty() || rest.starts_with('#') {
+        Ok(())
+    } else {
+        Err(format!("unexpected {}", rest))
+    }
+}
+
+// The end of a string that started just before `s`, skipping over escaped quotes.
+fn closing_quote(s: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            '"' if !escaped => return Some(i),
+            '\\' => escaped = !escaped,
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+impl FromStr for Answers {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Answers, String> {
+        let mut answers = Answers::default();
+        let mut lines = s.lines().enumerate();
+        while let Some((i, line)) = lines.next() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let at = |e: String| format!("line {}: {}", i + 1, e);
+            let eq = line
+                .find('=')
+                .ok_or_else(|| at(String::from("expected key = value")))?;
+            let (key, value) = (line[..eq].trim(), line[eq + 1..].trim());
+
+            let value = if let Some(rest) = value.strip_prefix("\"\"\"") {
+                // A newline straight after the opening quotes isn't part of the string.
+                let mut raw = String::from(rest);
+                let mut first = raw.is_empty();
+                while !raw.contains("\"\"\"") {
+                    let (_, next) = lines
+                        .next()
+                        .ok_or_else(|| at(String::from("unterminated multi-line string")))?;
+                    if !first {
+                        raw.push('\n');
+                    }
+                    first = false;
+                    raw.push_str(next);
+                }
+                let end = raw.find("\"\"\"").unwrap();
+                check_rest(&raw[end + 3..]).map_err(at)?;
+                unescape(&raw[..end]).map_err(at)?
+            } else if let Some(rest) = value.strip_prefix('"') {
+                let end =
+                    closing_quote(rest).ok_or_else(|| at(String::from("unterminated string")))?;
+                check_rest(&rest[end + 1..]).map_err(at)?;
+                unescape(&rest[..end]).map_err(at)?
+            } else {
+                let end = value.find('#').unwrap_or(value.len());
+                let n: i64 =
+                    value[..end].trim().replace('_', "").parse().map_err(|_| {
+                        at(format!("expected a number or a string, found {}", value))
+                    })?;
+                n.to_string()
+            };
+
+            let slot = match key {
+                "part1" => &mut answers.part1,
+                "part2" => &mut answers.part2,
+                _ => return Err(at(format!("unknown key {}", key))),
+            };
+            if slot.is_some() {
+                return Err(at(format!("{} is given twice", key)));
+            }
+            *slot = Some(value);
+        }
+        Ok(answers)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn smoke() {
+        let answers: Answers =
+            "# day 7\npart1 = \"IOFSJQDUWAPXELNVYZMHTBCRGK\" # order\n\npart2 = 9_31\n"
+                .parse()
+                .unwrap();
+        assert_eq!(answers.get(1), Some("IOFSJQDUWAPXELNVYZMHTBCRGK"));
+        assert_eq!(answers.get(2), Some("931"));
+        assert_eq!(
+            "part2 = \"a \\\"b\\\"\"".parse(),
+            Ok(Answers {
+                part1: None,
+                part2: Some(String::from("a \"b\"")),
+            })
+        );
+        assert_eq!("".parse(), Ok(Answers::default()));
+
+        assert!("part3 = 1".parse::<Answers>().is_err());
+        assert!("part1 = 1\npart1 = 2".parse::<Answers>().is_err());
+        assert!("part1 = one".parse::<Answers>().is_err());
+        assert!("part1 = \"one".parse::<Answers>().is_err());
+        assert!("part1 = \"one\" two".parse::<Answers>().is_err());
+        assert!("part1 = \"\\q\"".parse::<Answers>().is_err());
+    }
+
+    #[test]
+    fn multi_line() {
+        let answers: Answers = "part1 = \"\"\"\nx  x\nxxxx\n\"\"\"\npart2 = \"\"\"one\"\"\""
+            .parse()
+            .unwrap();
+        assert_eq!(answers.get(1), Some("x  x\nxxxx\n"));
+        assert_eq!(answers.get(2), Some("one"));
+        assert!("part1 = \"\"\"\nx".parse::<Answers>().is_err());
+
+        assert!(matches("x  x\nxxxx\n", "\nx  x   \nxxxx"));
+        assert!(!matches("x  x\nxxxx", "x x\nxxxx"));
+    }
+}
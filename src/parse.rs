@@ -0,0 +1,240 @@
+use std::error::Error;
+use std::fmt;
+
+// Why a line couldn't be parsed by a `from_pattern!` struct.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ParseError {
+    // The line didn't match the pattern at all.
+    NoMatch {
+        input: String,
+        pattern: &'static str,
+    },
+    // The line matched, but one of the captures isn't a valid value for its field.
+    Field {
+        input: String,
+        field: &'static str,
+        value: String,
+        reason: String,
+    },
+    // The line matched, but the pattern captured nothing for one of the fields. That happens when
+    // its group is optional, and was left out.
+    Missing {
+        input: String,
+        field: &'static str,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::NoMatch { input, pattern } => {
+                write!(f, "could not parse {:?}: expected {}", input, pattern)
+            }
+            ParseError::Field {
+                input,
+                field,
+                value,
+                reason,
+            } => write!(
+                f,
+                "could not parse {:?}: bad {} {:?} ({})",
+                input, field, value, reason
+            ),
+            ParseError::Missing { input, field } => {
+                write!(f, "could not parse {:?}: no {} given", input, field)
+            }
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+// The text captured for `field`.
+pub fn capture<'t>(
+    input: &str,
+    cap: &regex::Captures<'t>,
+    field: &'static str,
+) -> Result<&'t str, ParseError> {
+    cap.name(field)
+        .map(|m| m.as_str())
+        .ok_or_else(|| ParseError::Missing {
+            input: input.to_owned(),
+            field,
+        })
+}
+
+// The capture named `field`, parsed by `convert`.
+pub fn field<T, E, F>(
+    input: &str,
+    field: &'static str,
+    value: &str,
+    convert: F,
+) -> Result<T, ParseError>
+where
+    E: fmt::Display,
+    F: FnOnce(&str) -> Result<T, E>,
+{
+    convert(value).map_err(|e| ParseError::Field {
+        input: input.to_owned(),
+        field,
+        value: value.to_owned(),
+        reason: e.to_string(),
+    })
+}
+
+// A single ASCII character, as a byte. Handy for ids like the `C` in "Step C must be finished".
+pub fn byte(s: &str) -> Result<u8, String> {
+    match s.as_bytes() {
+        [b] if b.is_ascii() => Ok(*b),
+        _ => Err(String::from("expected a single ASCII character")),
+    }
+}
+
+// Declares a struct whose `FromStr` impl matches each line against `pattern`, and fills in each
+// field from the capture group with the same name. Fields are parsed with their own `FromStr`,
+// unless they're followed by `= convert`, in which case `convert(&str) -> Result<T, E>` is used.
+//
+//     from_pattern! {
+//         // Example: #37 @ 801,484: 22x28
+//         #[pattern = r"#(?P<id>\d+) @ (?P<x>\d+),(?P<y>\d+): (?P<w>\d+)x(?P<h>\d+)"]
+//         struct Rectangle { id: u32, x: u32, y: u32, w: u32, h: u32 }
+//     }
+#[macro_export]
+macro_rules! from_pattern {
+    (
+        #[pattern = $pattern:expr]
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $($fvis:vis $field:ident : $ty:ty $(= $convert:expr)?),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        $vis struct $name {
+            $($fvis $field: $ty),*
+        }
+
+        impl ::std::str::FromStr for $name {
+            type Err = $crate::parse::ParseError;
+
+            fn from_str(s: &str) -> Result<Self, $crate::parse::ParseError> {
+                ::lazy_static::lazy_static! {
+                    static ref PATTERN: ::regex::Regex = ::regex::Regex::new($pattern).unwrap();
+                }
+                let cap = PATTERN
+                    .captures(s)
+                    .ok_or_else(|| $crate::parse::ParseError::NoMatch {
+                        input: s.to_owned(),
+                        pattern: $pattern,
+                    })?;
+                Ok($name {
+                    $($field: $crate::from_pattern!(@field s, cap, $field, $ty $(, $convert)?)),*
+                })
+            }
+        }
+    };
+
+    (@field $s:ident, $cap:ident, $field:ident, $ty:ty) => {
+        $crate::parse::field(
+            $s,
+            stringify!($field),
+            $crate::parse::capture($s, &$cap, stringify!($field))?,
+            |v| v.parse::<$ty>(),
+        )?
+    };
+
+    (@field $s:ident, $cap:ident, $field:ident, $ty:ty, $convert:expr) => {
+        $crate::parse::field(
+            $s,
+            stringify!($field),
+            $crate::parse::capture($s, &$cap, stringify!($field))?,
+            $convert,
+        )?
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::geometry::Point;
+
+    from_pattern! {
+        // Example: move 3 from 1,2 as A
+        #[pattern = r"^move (?P<n>\d+) from (?P<at>[^ ]+) as (?P<tag>.)$"]
+        #[derive(Debug, Eq, PartialEq)]
+        struct Order {
+            n: u32,
+            at: Point,
+            tag: u8 = byte,
+        }
+    }
+
+    from_pattern! {
+        // Example: move 3 from 1,2 to 4,5
+        #[pattern = r"^move (?P<n>\d+) from (?P<from>[^ ]+)(?: to (?P<to>[^ ]+))?$"]
+        #[derive(Debug)]
+        struct Delivery {
+            n: u32,
+            from: Point,
+            to: Point,
+        }
+    }
+
+    #[test]
+    fn smoke() {
+        assert_eq!(
+            "move 3 from 1,2 as A".parse(),
+            Ok(Order {
+                n: 3,
+                at: Point(1, 2),
+                tag: b'A',
+            })
+        );
+
+        let err = "move three from 1,2 as A".parse::<Order>().unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::NoMatch {
+                input: String::from("move three from 1,2 as A"),
+                pattern: r"^move (?P<n>\d+) from (?P<at>[^ ]+) as (?P<tag>.)$",
+            }
+        );
+
+        let err = "move 3 from 1,x as A".parse::<Order>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            r#"could not parse "move 3 from 1,x as A": bad at "1,x" (could not parse 1,x)"#
+        );
+
+        let err = "move 99999999999 from 1,2 as A"
+            .parse::<Order>()
+            .unwrap_err();
+        match err {
+            ParseError::Field { field, value, .. } => {
+                assert_eq!((field, value.as_str()), ("n", "99999999999"))
+            }
+            _ => panic!("expected a field error, got {:?}", err),
+        }
+
+        let err = "move 3 from 1,2".parse::<Delivery>().unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::Missing {
+                input: String::from("move 3 from 1,2"),
+                field: "to",
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            r#"could not parse "move 3 from 1,2": no to given"#
+        );
+        assert_eq!(
+            "move 3 from 1,2 to 4,5".parse::<Delivery>().map(|d| d.to),
+            Ok(Point(4, 5))
+        );
+
+        assert_eq!(
+            byte("é"),
+            Err(String::from("expected a single ASCII character"))
+        );
+    }
+}
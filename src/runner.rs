@@ -0,0 +1,547 @@
+use crate::manifest::{self, Answers};
+use crate::{
+    day1, day10, day11, day12, day13, day14, day15, day16, day17, day18, day19, day2, day20, day21,
+    day22, day23, day24, day25, day3, day5, day6, day7, day8, day9,
+};
+use std::fmt::{Display, Write};
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// A day's solution. The input is parsed from scratch for each part, so that each part can be
+// timed (and run) on its own.
+pub trait Puzzle {
+    type Input;
+
+    // How many parts the puzzle has. Only `part1` is ever run when it's 1.
+    const PARTS: u32 = 2;
+
+    fn parse(s: &str) -> Result<Self::Input, String>;
+
+    fn part1(input: &Self::Input) -> Result<String, String>;
+
+    fn part2(input: &Self::Input) -> Result<String, String>;
+}
+
+// Parses every non-blank line of `s`, and says which line it was if one of them is bad.
+pub fn parse_lines<T>(s: &str) -> Result<Vec<T>, String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    s.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| line.parse().map_err(|e| format!("line {}: {}", i + 1, e)))
+        .collect()
+}
+
+// One part of one day, along with how to run it.
+#[derive(Clone, Copy)]
+pub struct Solution {
+    pub day: u32,
+    pub part: u32,
+    solve: fn(&str, u32) -> Report,
+}
+
+fn timed<P: Puzzle>(s: &str, part: u32) -> Report {
+    let start = Instant::now();
+    let input = P::parse(s);
+    let parse = start.elapsed();
+
+    let start = Instant::now();
+    let answer = input.and_then(|input| match part {
+        1 => P::part1(&input),
+        _ => P::part2(&input),
+    });
+    Report {
+        day: 0,
+        part,
+        input: String::new(),
+        answer,
+        parse,
+        solve: start.elapsed(),
+    }
+}
+
+// Runs one part of `P` on the day's usual input, and checks the answer against its manifest. The
+// answers to real inputs live only in the manifests, so tests use this rather than repeating them.
+#[cfg(test)]
+pub(crate) fn check_answer<P: Puzzle>(day: u32, part: u32) {
+    let solution = Solution {
+        day,
+        part,
+        solve: timed::<P>,
+    };
+    let report = solution.run(&solution.input_path());
+    assert_eq!(report.verdict(), Verdict::Pass, "{:?}", report.answer);
+}
+
+fn puzzle<P: Puzzle>(day: u32) -> Vec<Solution> {
+    (1..=P::PARTS)
+        .map(|part| Solution {
+            day,
+            part,
+            solve: timed::<P>,
+        })
+        .collect()
+}
+
+// Every day there's a solution for, in order.
+pub fn solutions() -> Vec<Solution> {
+    vec![
+        puzzle::<day1::Day1>(1),
+        puzzle::<day2::Day2>(2),
+        puzzle::<day3::Day3>(3),
+        puzzle::<day5::Day5>(5),
+        puzzle::<day6::Day6>(6),
+        puzzle::<day7::Day7>(7),
+        puzzle::<day8::Day8>(8),
+        puzzle::<day9::Day9>(9),
+        puzzle::<day10::Day10>(10),
+        puzzle::<day11::Day11>(11),
+        puzzle::<day12::Day12>(12),
+        puzzle::<day13::Day13>(13),
+        puzzle::<day14::Day14>(14),
+        puzzle::<day15::Day15>(15),
+        puzzle::<day16::Day16>(16),
+        puzzle::<day17::Day17>(17),
+        puzzle::<day18::Day18>(18),
+        puzzle::<day19::Day19>(19),
+        puzzle::<day20::Day20>(20),
+        puzzle::<day21::Day21>(21),
+        puzzle::<day22::Day22>(22),
+        puzzle::<day23::Day23>(23),
+        puzzle::<day24::Day24>(24),
+        puzzle::<day25::Day25>(25),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+impl Solution {
+    pub fn input_path(&self) -> String {
+        format!("data/day{}/input", self.day)
+    }
+
+    // Whether the day's usual input is checked in. Not every day's is.
+    pub fn has_input(&self) -> bool {
+        Path::new(&self.input_path()).is_file()
+    }
+
+    // Every input in the day's data directory, including any that teammates have dropped in next
+    // to the usual one. Anything ending in `.toml` is a manifest of answers, not an input.
+    pub fn inputs(&self) -> Vec<String> {
+        let mut inputs: Vec<String> = fs::read_dir(format!("data/day{}", self.day))
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && path.extension().is_none_or(|ext| ext != "toml"))
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
+        inputs.sort();
+        inputs
+    }
+
+    // Runs against the input at `path`. A missing input, a bad input, or a panic all end up as an
+    // error in the report rather than stopping the whole run.
+    pub fn run(&self, path: &str) -> Report {
+        let mut report = match fs::read_to_string(path) {
+            Ok(s) => panic::catch_unwind(AssertUnwindSafe(|| (self.solve)(&s, self.part)))
+                .unwrap_or_else(|_| Report::failed(self.part, String::from("panicked"))),
+            Err(e) => Report::failed(self.part, format!("could not read {}: {}", path, e)),
+        };
+        report.day = self.day;
+        report.input = String::from(path);
+        report
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Report {
+    pub day: u32,
+    pub part: u32,
+    pub input: String,
+    pub answer: Result<String, String>,
+    pub parse: Duration,
+    pub solve: Duration,
+}
+
+impl Report {
+    fn failed(part: u32, reason: String) -> Report {
+        Report {
+            day: 0,
+            part,
+            input: String::new(),
+            answer: Err(reason),
+            parse: Duration::default(),
+            solve: Duration::default(),
+        }
+    }
+
+    pub fn total(&self) -> Duration {
+        self.parse + self.solve
+    }
+
+    // Checks the answer against the manifest next to the input, if there is one.
+    pub fn verdict(&self) -> Verdict {
+        let path = Answers::path(&self.input);
+        let answers: Answers = match fs::read_to_string(&path) {
+            Ok(s) => match s.parse() {
+                Ok(answers) => answers,
+                Err(e) => return Verdict::Fail(format!("bad manifest {}: {}", path, e)),
+            },
+            Err(_) => return Verdict::Unknown,
+        };
+        match (answers.get(self.part), &self.answer) {
+            (None, _) => Verdict::Unknown,
+            (Some(expected), Ok(actual)) if manifest::matches(expected, actual) => Verdict::Pass,
+            (Some(expected), Ok(_)) => Verdict::Fail(format!("expected {}", summary(expected))),
+            (Some(_), Err(e)) => Verdict::Fail(e.clone()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Verdict {
+    Pass,
+    // Why it failed: either the right answer, or what went wrong along the way.
+    Fail(String),
+    // There's no known answer to compare against.
+    Unknown,
+}
+
+impl Verdict {
+    fn name(&self) -> &'static str {
+        match self {
+            Verdict::Pass => "pass",
+            Verdict::Fail(_) => "fail",
+            Verdict::Unknown => "unknown",
+        }
+    }
+}
+
+pub struct Run {
+    pub reports: Vec<Report>,
+    pub wall: Duration,
+}
+
+// Runs each solution against its input, either one after another or spread over one worker per
+// CPU. The reports are in the same order as `jobs` either way.
+pub fn run(jobs: &[(Solution, String)], parallel: bool) -> Run {
+    let start = Instant::now();
+    let reports = if parallel {
+        let workers = thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(jobs.len());
+        // Each worker takes the next job nobody has started yet, until there are none left.
+        let next = AtomicUsize::new(0);
+        let done: Mutex<Vec<Option<Report>>> = Mutex::new(vec![None; jobs.len()]);
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let (solution, path) = match jobs.get(i) {
+                        Some(job) => job,
+                        None => break,
+                    };
+                    let report = solution.run(path);
+                    done.lock().unwrap()[i] = Some(report);
+                });
+            }
+        });
+        done.into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|report| report.expect("every job is run"))
+            .collect()
+    } else {
+        jobs.iter()
+            .map(|(solution, path)| solution.run(path))
+            .collect()
+    };
+    Run {
+        reports,
+        wall: start.elapsed(),
+    }
+}
+
+fn millis(d: Duration) -> f64 {
+    d.as_secs() as f64 * 1e3 + f64::from(d.subsec_nanos()) / 1e6
+}
+
+fn json_string(s: &str) -> String {
+    let mut buf = String::with_capacity(s.len() + 2);
+    buf.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => buf.push_str("\\\""),
+            '\\' => buf.push_str("\\\\"),
+            '\n' => buf.push_str("\\n"),
+            '\t' => buf.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(buf, "\\u{:04x}", c as u32).unwrap(),
+            c => buf.push(c),
+        }
+    }
+    buf.push('"');
+    buf
+}
+
+// An answer short enough for a table cell.
+fn summary(answer: &str) -> String {
+    if answer.contains('\n') {
+        format!("({} lines)", answer.lines().count())
+    } else {
+        String::from(answer)
+    }
+}
+
+fn outcome(r: &Report) -> String {
+    match &r.answer {
+        Ok(answer) => summary(answer),
+        Err(e) => format!("error: {}", e),
+    }
+}
+
+impl Run {
+    // One row per part. Answers that span several lines (like day 10's message) are printed in
+    // full underneath their row.
+    pub fn table(&self) -> String {
+        let width = self
+            .reports
+            .iter()
+            .map(|r| outcome(r).len())
+            .chain(Some("answer".len()))
+            .max()
+            .unwrap();
+
+        let mut buf = String::new();
+        writeln!(
+            buf,
+            "day part  {:<w$}  {:>10}  {:>10}  {:>10}",
+            "answer",
+            "parse (ms)",
+            "solve (ms)",
+            "total (ms)",
+            w = width
+        )
+        .unwrap();
+        for r in &self.reports {
+            writeln!(
+                buf,
+                "{:>3} {:>4}  {:<w$}  {:>10.3}  {:>10.3}  {:>10.3}",
+                r.day,
+                r.part,
+                outcome(r),
+                millis(r.parse),
+                millis(r.solve),
+                millis(r.total()),
+                w = width
+            )
+            .unwrap();
+            if let Ok(answer) = &r.answer {
+                if answer.contains('\n') {
+                    for line in answer.lines() {
+                        writeln!(buf, "          {}", line).unwrap();
+                    }
+                }
+            }
+        }
+        let busy: Duration = self.reports.iter().map(Report::total).sum();
+        writeln!(
+            buf,
+            "{:.3} ms in total, {:.3} ms wall clock",
+            millis(busy),
+            millis(self.wall)
+        )
+        .unwrap();
+        buf
+    }
+
+    pub fn json(&self) -> String {
+        let rows: Vec<String> = self
+            .reports
+            .iter()
+            .map(|r| {
+                let (answer, error) = match &r.answer {
+                    Ok(answer) => (json_string(answer), String::from("null")),
+                    Err(e) => (String::from("null"), json_string(e)),
+                };
+                format!(
+                    "{{\"day\": {}, \"part\": {}, \"input\": {}, \"answer\": {}, \"error\": {}, \"verdict\": \"{}\", \"parse_ms\": {:.3}, \"solve_ms\": {:.3}, \"total_ms\": {:.3}}}",
+                    r.day,
+                    r.part,
+                    json_string(&r.input),
+                    answer,
+                    error,
+                    r.verdict().name(),
+                    millis(r.parse),
+                    millis(r.solve),
+                    millis(r.total())
+                )
+            })
+            .collect();
+        format!(
+            "{{\"wall_ms\": {:.3}, \"results\": [\n  {}\n]}}\n",
+            millis(self.wall),
+            rows.join(",\n  ")
+        )
+    }
+
+    // One row per part and input, saying whether the answer matched the manifest.
+    pub fn verification(&self) -> String {
+        let verdicts: Vec<Verdict> = self.reports.iter().map(Report::verdict).collect();
+        let width = self
+            .reports
+            .iter()
+            .map(|r| r.input.len())
+            .chain(Some("input".len()))
+            .max()
+            .unwrap();
+
+        let mut buf = String::new();
+        writeln!(buf, "day part  {:<w$}  verdict  answer", "input", w = width).unwrap();
+        for (r, verdict) in self.reports.iter().zip(&verdicts) {
+            write!(
+                buf,
+                "{:>3} {:>4}  {:<w$}  {:<7}  {}",
+                r.day,
+                r.part,
+                r.input,
+                verdict.name(),
+                outcome(r),
+                w = width
+            )
+            .unwrap();
+            match verdict {
+                Verdict::Fail(reason) if r.answer.is_ok() => writeln!(buf, " ({})", reason),
+                _ => writeln!(buf),
+            }
+            .unwrap();
+        }
+        let count = |name| verdicts.iter().filter(|v| v.name() == name).count();
+        writeln!(
+            buf,
+            "{} passed, {} failed, {} unknown",
+            count("pass"),
+            count("fail"),
+            count("unknown")
+        )
+        .unwrap();
+        buf
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Sum;
+
+    impl Puzzle for Sum {
+        type Input = Vec<i32>;
+
+        fn parse(s: &str) -> Result<Vec<i32>, String> {
+            parse_lines(s)
+        }
+
+        fn part1(xs: &Vec<i32>) -> Result<String, String> {
+            Ok(xs.iter().sum::<i32>().to_string())
+        }
+
+        fn part2(_: &Vec<i32>) -> Result<String, String> {
+            panic!("not implemented")
+        }
+    }
+
+    #[test]
+    fn smoke() {
+        assert_eq!(timed::<Sum>("1\n\n2\n3\n", 1).answer, Ok(String::from("6")));
+        assert_eq!(
+            timed::<Sum>("1\nx\n", 1).answer,
+            Err(String::from("line 2: invalid digit found in string"))
+        );
+
+        let solutions = [
+            Solution {
+                day: 1,
+                part: 2,
+                solve: timed::<Sum>,
+            },
+            Solution {
+                day: 4,
+                part: 1,
+                solve: timed::<Sum>,
+            },
+        ];
+        let jobs: Vec<(Solution, String)> =
+            solutions.iter().map(|&s| (s, s.input_path())).collect();
+        let run = run(&jobs, true);
+        assert_eq!(run.reports[0].answer, Err(String::from("panicked")));
+        assert_eq!((run.reports[1].day, run.reports[1].part), (4, 1));
+        assert!(run.reports[1].answer.is_err());
+        assert_eq!(run.reports[1].verdict(), Verdict::Unknown);
+        assert!(run
+            .json()
+            .contains("\"day\": 4, \"part\": 1, \"input\": \"data/day4/input\", \"answer\": null"));
+    }
+
+    #[test]
+    fn escaping() {
+        assert_eq!(json_string("a\"b\\c\nd\u{1}"), r#""a\"b\\c\nd\u0001""#);
+    }
+
+    #[test]
+    fn registered() {
+        let solutions = solutions();
+        // Every day with an input checked in has a solution to run against it.
+        for entry in fs::read_dir("data").unwrap() {
+            let name = entry.unwrap().file_name().into_string().unwrap();
+            let day: u32 = name.trim_start_matches("day").parse().unwrap();
+            assert!(solutions.iter().any(|s| s.day == day), "{}", name);
+        }
+        assert_eq!(solutions.iter().filter(|s| s.day == 25).count(), 1);
+        assert!(solutions
+            .windows(2)
+            .all(|w| (w[0].day, w[0].part) < (w[1].day, w[1].part)));
+    }
+
+    #[test]
+    fn verify() {
+        let report = |input: &str, answer: &str| Report {
+            day: 1,
+            part: 2,
+            input: String::from(input),
+            answer: Ok(String::from(answer)),
+            parse: Duration::default(),
+            solve: Duration::default(),
+        };
+        assert_eq!(report("data/day1/input", "481").verdict(), Verdict::Pass);
+        assert_eq!(
+            report("data/day1/input", "482").verdict(),
+            Verdict::Fail(String::from("expected 481"))
+        );
+        assert_eq!(
+            report("data/day1/missing", "481").verdict(),
+            Verdict::Unknown
+        );
+
+        // Every input that's checked in has its answers next to it.
+        for solution in solutions() {
+            for input in solution.inputs() {
+                let answers: Answers = fs::read_to_string(Answers::path(&input))
+                    .expect("read manifest")
+                    .parse()
+                    .expect("parse manifest");
+                assert!(answers.get(solution.part).is_some(), "{}", input);
+            }
+        }
+    }
+}
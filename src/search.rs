@@ -0,0 +1,443 @@
+use crate::geometry::{BoundingBox, Point};
+use crate::grid::Grid;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+
+// Everything a search found: how far away each node it reached was, and how it got there.
+#[derive(Debug, Clone)]
+pub struct Reached<N: Hash + Eq> {
+    dist: HashMap<N, usize>,
+    parent: HashMap<N, N>,
+    // The first node the search reached that satisfied its goal, if any did.
+    pub goal: Option<N>,
+    // Whether the search gave up after hitting its visit limit.
+    pub truncated: bool,
+}
+
+impl<N: Clone + Hash + Eq> Reached<N> {
+    fn new() -> Reached<N> {
+        Reached {
+            dist: HashMap::new(),
+            parent: HashMap::new(),
+            goal: None,
+            truncated: false,
+        }
+    }
+
+    pub fn distance(&self, n: &N) -> Option<usize> {
+        self.dist.get(n).cloned()
+    }
+
+    pub fn distances(&self) -> &HashMap<N, usize> {
+        &self.dist
+    }
+
+    pub fn into_distances(self) -> HashMap<N, usize> {
+        self.dist
+    }
+
+    pub fn contains(&self, n: &N) -> bool {
+        self.dist.contains_key(n)
+    }
+
+    pub fn len(&self) -> usize {
+        self.dist.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dist.is_empty()
+    }
+
+    // The nodes along a shortest path from one of the starting nodes to `n`, inclusive.
+    pub fn path_to(&self, n: &N) -> Option<Vec<N>> {
+        if !self.dist.contains_key(n) {
+            return None;
+        }
+        let mut path = vec![n.clone()];
+        while let Some(prev) = self.parent.get(path.last().unwrap()) {
+            path.push(prev.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+// Breadth-first search outwards from every node in `starts` at once, until it reaches a node
+// where `goal` holds, runs out of nodes, or has expanded `limit` nodes.
+pub fn bfs<N, I, F, J, G>(starts: I, mut neighbors: F, goal: G, limit: Option<usize>) -> Reached<N>
+where
+    N: Clone + Hash + Eq,
+    I: IntoIterator<Item = N>,
+    F: FnMut(&N) -> J,
+    J: IntoIterator<Item = N>,
+    G: Fn(&N) -> bool,
+{
+    let mut reached = Reached::new();
+    let mut q = VecDeque::new();
+    for start in starts {
+        if !reached.dist.contains_key(&start) {
+            reached.dist.insert(start.clone(), 0);
+            q.push_back(start);
+        }
+    }
+
+    let mut expanded = 0;
+    while let Some(cur) = q.pop_front() {
+        if goal(&cur) {
+            reached.goal = Some(cur);
+            break;
+        }
+        if limit.is_some_and(|limit| expanded >= limit) {
+            reached.truncated = true;
+            break;
+        }
+        expanded += 1;
+        let d = reached.dist[&cur];
+        for n in neighbors(&cur) {
+            if !reached.dist.contains_key(&n) {
+                reached.dist.insert(n.clone(), d + 1);
+                reached.parent.insert(n.clone(), cur.clone());
+                q.push_back(n);
+            }
+        }
+    }
+    reached
+}
+
+// A breadth-first search from all of `sources` at once, which works out which source each node
+// is closest to. Nodes equally close to more than one source map to `None`.
+pub fn nearest_sources<N, I, F, J>(sources: I, mut neighbors: F) -> HashMap<N, Option<N>>
+where
+    N: Clone + Hash + Eq,
+    I: IntoIterator<Item = N>,
+    F: FnMut(&N) -> J,
+    J: IntoIterator<Item = N>,
+{
+    let mut nearest: HashMap<N, Option<N>> = HashMap::new();
+    let mut dist: HashMap<N, usize> = HashMap::new();
+    let mut q = VecDeque::new();
+    for source in sources {
+        if !dist.contains_key(&source) {
+            dist.insert(source.clone(), 0);
+            nearest.insert(source.clone(), Some(source.clone()));
+            q.push_back(source);
+        }
+    }
+
+    // Every node one step further out is seen from every one of its neighbours before it's
+    // expanded itself, so by then it knows whether they all agree on a source.
+    while let Some(cur) = q.pop_front() {
+        let d = dist[&cur];
+        let origin = nearest[&cur].clone();
+        for n in neighbors(&cur) {
+            match dist.get(&n) {
+                None => {
+                    dist.insert(n.clone(), d + 1);
+                    nearest.insert(n.clone(), origin.clone());
+                    q.push_back(n);
+                }
+                Some(&nd) if nd == d + 1 && nearest[&n] != origin => {
+                    nearest.insert(n, None);
+                }
+                Some(_) => {}
+            }
+        }
+    }
+    nearest
+}
+
+// `nearest_sources` for every point in `bounds`, moving between adjacent points. Keeping it all in
+// a grid makes this far quicker than hashing each point. Each point maps to the index in `sources`
+// of the one closest to it, or `None` for a tie. Sources outside `bounds` are ignored.
+pub fn nearest_sources_in(bounds: &BoundingBox<Point>, sources: &[Point]) -> Grid<Option<usize>> {
+    let mut nearest = Grid::from_bounds(bounds, None);
+    let mut dist = Grid::from_bounds(bounds, usize::MAX);
+    let mut q = VecDeque::new();
+    for (i, &source) in sources.iter().enumerate() {
+        if dist.get(source) == Some(&usize::MAX) {
+            dist[source] = 0;
+            nearest[source] = Some(i);
+            q.push_back(source);
+        }
+    }
+
+    while let Some(cur) = q.pop_front() {
+        let d = dist[cur];
+        let origin = nearest[cur];
+        for n in cur.neighbors() {
+            match dist.get(n) {
+                Some(&usize::MAX) => {
+                    dist[n] = d + 1;
+                    nearest[n] = origin;
+                    q.push_back(n);
+                }
+                Some(&nd) if nd == d + 1 && nearest[n] != origin => nearest[n] = None,
+                _ => {}
+            }
+        }
+    }
+    nearest
+}
+
+// A best-first search where moving to each neighbour has a cost, ordered by the cost so far plus
+// `heuristic`. The heuristic must never overestimate the remaining cost to a goal.
+pub fn astar<N, I, F, J, H, G>(
+    starts: I,
+    mut neighbors: F,
+    heuristic: H,
+    goal: G,
+    limit: Option<usize>,
+) -> Reached<N>
+where
+    N: Clone + Hash + Eq,
+    I: IntoIterator<Item = N>,
+    F: FnMut(&N) -> J,
+    J: IntoIterator<Item = (N, usize)>,
+    H: Fn(&N) -> usize,
+    G: Fn(&N) -> bool,
+{
+    let mut reached = Reached::new();
+    // The heap holds indices into `nodes`, so that nodes themselves don't need to be ordered.
+    let mut nodes: Vec<N> = Vec::new();
+    let mut q = BinaryHeap::new();
+    for start in starts {
+        if !reached.dist.contains_key(&start) {
+            reached.dist.insert(start.clone(), 0);
+            q.push(Reverse((heuristic(&start), 0, nodes.len())));
+            nodes.push(start);
+        }
+    }
+
+    let mut expanded = 0;
+    while let Some(Reverse((_, d, idx))) = q.pop() {
+        let cur = nodes[idx].clone();
+        if reached.dist[&cur] < d {
+            continue;
+        }
+        if goal(&cur) {
+            reached.goal = Some(cur);
+            break;
+        }
+        if limit.is_some_and(|limit| expanded >= limit) {
+            reached.truncated = true;
+            break;
+        }
+        expanded += 1;
+        for (n, cost) in neighbors(&cur) {
+            let nd = d + cost;
+            if reached.dist.get(&n).is_none_or(|&old| nd < old) {
+                reached.dist.insert(n.clone(), nd);
+                reached.parent.insert(n.clone(), cur.clone());
+                q.push(Reverse((nd + heuristic(&n), nd, nodes.len())));
+                nodes.push(n);
+            }
+        }
+    }
+    reached
+}
+
+// The same as `astar`, with no heuristic at all.
+pub fn dijkstra<N, I, F, J, G>(starts: I, neighbors: F, goal: G, limit: Option<usize>) -> Reached<N>
+where
+    N: Clone + Hash + Eq,
+    I: IntoIterator<Item = N>,
+    F: FnMut(&N) -> J,
+    J: IntoIterator<Item = (N, usize)>,
+    G: Fn(&N) -> bool,
+{
+    astar(starts, neighbors, |_| 0, goal, limit)
+}
+
+// Kahn's algorithm, one step at a time. Whenever several nodes are ready, the smallest comes
+// first. Nodes only become ready once everything before them has been completed, which lets
+// callers schedule the work however they like.
+pub struct TopologicalOrder<N: Ord + Hash + Eq> {
+    successors: HashMap<N, Vec<N>>,
+    in_degree: HashMap<N, usize>,
+    ready: BinaryHeap<Reverse<N>>,
+}
+
+impl<N: Clone + Ord + Hash + Eq> TopologicalOrder<N> {
+    pub fn new<I, F, J>(nodes: I, mut successors: F) -> TopologicalOrder<N>
+    where
+        I: IntoIterator<Item = N>,
+        F: FnMut(&N) -> J,
+        J: IntoIterator<Item = N>,
+    {
+        let mut order = TopologicalOrder {
+            successors: HashMap::new(),
+            in_degree: HashMap::new(),
+            ready: BinaryHeap::new(),
+        };
+        for n in nodes {
+            order.in_degree.entry(n.clone()).or_insert(0);
+            let next: Vec<N> = successors(&n).into_iter().collect();
+            for m in &next {
+                *order.in_degree.entry(m.clone()).or_insert(0) += 1;
+            }
+            order.successors.entry(n).or_default().extend(next);
+        }
+        for (n, &count) in order.in_degree.iter() {
+            if count == 0 {
+                order.ready.push(Reverse(n.clone()));
+            }
+        }
+        order
+    }
+
+    // The smallest node that's ready to go.
+    pub fn next_ready(&mut self) -> Option<N> {
+        self.ready.pop().map(|Reverse(n)| n)
+    }
+
+    // Marks `n` as done, which may make some of its successors ready.
+    pub fn complete(&mut self, n: &N) {
+        for m in self.successors.get(n).into_iter().flatten() {
+            let count = self.in_degree.get_mut(m).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                self.ready.push(Reverse(m.clone()));
+            }
+        }
+    }
+
+    // How many nodes there are in total.
+    pub fn len(&self) -> usize {
+        self.in_degree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.in_degree.is_empty()
+    }
+}
+
+// Every node, each one after all of its predecessors, taking the smallest available node first.
+// Returns `None` if there's a cycle, since then there's no such order.
+pub fn topological_sort<N, I, F, J>(nodes: I, successors: F) -> Option<Vec<N>>
+where
+    N: Clone + Ord + Hash + Eq,
+    I: IntoIterator<Item = N>,
+    F: FnMut(&N) -> J,
+    J: IntoIterator<Item = N>,
+{
+    let mut order = TopologicalOrder::new(nodes, successors);
+    let mut sorted = Vec::with_capacity(order.len());
+    while let Some(n) = order.next_ready() {
+        order.complete(&n);
+        sorted.push(n);
+    }
+    if sorted.len() == order.len() {
+        Some(sorted)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // A 10x10 grid with a wall down x = 5, apart from a gap at y = 9.
+    fn open_neighbors(&(x, y): &(i32, i32)) -> Vec<(i32, i32)> {
+        vec![(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]
+            .into_iter()
+            .filter(|&(x, y)| (0..10).contains(&x) && (0..10).contains(&y) && (x != 5 || y == 9))
+            .collect()
+    }
+
+    #[test]
+    fn breadth_first() {
+        let reached = bfs(vec![(0, 0)], open_neighbors, |_| false, None);
+        assert_eq!(reached.len(), 91);
+        assert!(!reached.truncated);
+        assert_eq!(reached.distance(&(9, 0)), Some(9 + 9 + 9));
+        let path = reached.path_to(&(9, 0)).unwrap();
+        assert_eq!(path.len(), 28);
+        assert_eq!((path[0], path[27]), ((0, 0), (9, 0)));
+        assert!(path.contains(&(5, 9)));
+        assert_eq!(reached.path_to(&(5, 0)), None);
+
+        let found = bfs(vec![(0, 0)], open_neighbors, |&p| p == (4, 0), None);
+        assert_eq!(found.goal, Some((4, 0)));
+        assert_eq!(found.distance(&(4, 0)), Some(4));
+
+        let limited = bfs(vec![(0, 0)], open_neighbors, |_| false, Some(10));
+        assert!(limited.truncated);
+        assert!(limited.len() < 91);
+
+        let both = bfs(vec![(0, 0), (9, 0)], open_neighbors, |_| false, None);
+        assert_eq!(both.distance(&(6, 0)), Some(3));
+        assert_eq!(both.path_to(&(6, 0)).unwrap()[0], (9, 0));
+    }
+
+    #[test]
+    fn nearest() {
+        let line = |&x: &i32| {
+            vec![x - 1, x + 1]
+                .into_iter()
+                .filter(|n| (0..=10).contains(n))
+                .collect::<Vec<_>>()
+        };
+        let nearest = nearest_sources(vec![2, 6], line);
+        assert_eq!(nearest[&0], Some(2));
+        assert_eq!(nearest[&3], Some(2));
+        assert_eq!(nearest[&4], None);
+        assert_eq!(nearest[&5], Some(6));
+        assert_eq!(nearest[&10], Some(6));
+
+        let bounds = BoundingBox {
+            min: Point(0, 0),
+            max: Point(4, 2),
+        };
+        let dense = nearest_sources_in(&bounds, &[Point(0, 0), Point(4, 2), Point(9, 9)]);
+        assert_eq!(
+            dense.render(|n| match n {
+                Some(i) => (b'a' + *i as u8) as char,
+                None => '.',
+            }),
+            "aaa.b\naa.bb\na.bbb\n"
+        );
+    }
+
+    #[test]
+    fn weighted() {
+        // Moving right is cheap, and moving in any other direction is expensive.
+        let costs = |&p: &(i32, i32)| {
+            open_neighbors(&p)
+                .into_iter()
+                .map(move |n| (n, if n.0 > p.0 { 1 } else { 3 }))
+                .collect::<Vec<_>>()
+        };
+        let goal = |&p: &(i32, i32)| p == (9, 0);
+        let plain = dijkstra(vec![(0, 0)], costs, goal, None);
+        assert_eq!(plain.goal, Some((9, 0)));
+        assert_eq!(plain.distance(&(9, 0)), Some(9 + 3 * 18));
+
+        let manhattan = |&(x, y): &(i32, i32)| ((9 - x).abs() + y.abs()) as usize;
+        let guided = astar(vec![(0, 0)], costs, manhattan, goal, None);
+        assert_eq!(guided.distance(&(9, 0)), Some(9 + 3 * 18));
+        assert!(guided.len() <= plain.len());
+        let path = guided.path_to(&(9, 0)).unwrap();
+        assert_eq!(path.len(), 28);
+    }
+
+    #[test]
+    fn topological() {
+        let edges: HashMap<char, Vec<char>> = vec![
+            ('C', vec!['A', 'F']),
+            ('A', vec!['B', 'D']),
+            ('B', vec!['E']),
+            ('D', vec!['E']),
+            ('F', vec!['E']),
+        ]
+        .into_iter()
+        .collect();
+        let successors = |c: &char| edges.get(c).cloned().unwrap_or_default();
+        assert_eq!(
+            topological_sort("ABCDEF".chars(), successors),
+            Some("CABDFE".chars().collect())
+        );
+        assert_eq!(topological_sort(vec![1, 2, 3], |&n| vec![n % 3 + 1]), None);
+    }
+}